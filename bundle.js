@@ -1,14 +1,38 @@
 import * as sim from './pkg/simulation_wasm.js';
 import { draw_triangle, draw_circle,  high_res_ctx } from './utils.js';
 
-const simulation = new sim.Simulation();
+// Open with ?seed=<number> to replay a previous run, and with
+// ?config=<json> to change settings, e.g. {"world_predators":5}
+const params = new URLSearchParams(window.location.search);
+const seed = params.get('seed');
+const config = params.get('config');
+const simulation = config !== null
+    ? sim.Simulation.withConfig(BigInt(seed ?? Math.floor(Math.random() * 2 ** 32)), config)
+    : seed === null
+        ? new sim.Simulation()
+        : sim.Simulation.withSeed(BigInt(seed));
+
+console.log(`seed: ${simulation.seed()}`);
 const viewport = document.getElementById('viewport');
 
 CanvasRenderingContext2D.prototype.drawTriangle = draw_triangle;
 CanvasRenderingContext2D.prototype.drawCircle = draw_circle;
 
 document.getElementById('train').onclick = () => {
-    console.log(simulation.train());
+    const stats = simulation.train();
+    const percentiles = stats.percentiles
+        .map((p) => `p${p.percentile}=${p.fitness.toFixed(2)}`)
+        .join(', ');
+
+    console.log(
+        `min=${stats.min_fitness.toFixed(2)}, ` +
+        `max=${stats.max_fitness.toFixed(2)}, ` +
+        `avg=${stats.avg_fitness.toFixed(2)}, ` +
+        `median=${stats.median_fitness.toFixed(2)}, ` +
+        `std_dev=${stats.std_dev_fitness.toFixed(2)}, ` +
+        `${percentiles}, ` +
+        `diversity=${stats.diversity.toFixed(3)}`
+    );
 }
 
 const viewportWidth = viewport.width;
@@ -31,6 +55,11 @@ function redraw() {
     }
 
     for (const animal of world.animals) {
+        // Caught or starved
+        if (!animal.alive) {
+            continue;
+        }
+
         ctx.drawTriangle(
             animal.x * viewportWidth,
             animal.y * viewportHeight,
@@ -42,6 +71,20 @@ function redraw() {
         )
     }
 
+    for (const predator of world.predators) {
+        if (!predator.alive) {
+            continue;
+        }
+
+        ctx.drawTriangle(
+            predator.x * viewportWidth,
+            predator.y * viewportHeight,
+            0.03 * viewport.width,
+            predator.rotation - Math.PI / 2,
+            'rgb(255, 64, 64)',
+        )
+    }
+
     requestAnimationFrame(redraw);
 }
 
@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Runs the simulation without a browser, printing per-generation statistics
+
+Usage: birdievo [OPTIONS]
+
+Options:
+  --seed <u64>          Seed for the random number generator [default: 0]
+  --generations <n>     Number of generations to train [default: 100]
+  --config <path>       Simulation config, .toml or .json [default: built-in]
+  --format <csv|jsonl>  Output format [default: csv]
+  --output <path>       Write statistics to a file instead of stdout
+  --help                Print this message";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Csv,
+    JsonLines,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Args {
+    pub seed: u64,
+    pub generations: usize,
+    pub config: Option<PathBuf>,
+    pub format: Format,
+    pub output: Option<PathBuf>,
+    pub help: bool,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            generations: 100,
+            config: None,
+            format: Format::Csv,
+            output: None,
+            help: false,
+        }
+    }
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                parsed.help = true;
+                continue;
+            }
+
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {arg}"))
+            };
+
+            match arg.as_str() {
+                "--seed" => {
+                    parsed.seed = value()?
+                        .parse()
+                        .map_err(|err| format!("invalid --seed: {err}"))?;
+                }
+                "--generations" => {
+                    parsed.generations = value()?
+                        .parse()
+                        .map_err(|err| format!("invalid --generations: {err}"))?;
+                }
+                "--config" => parsed.config = Some(value()?.into()),
+                "--format" => {
+                    parsed.format = match value()?.as_str() {
+                        "csv" => Format::Csv,
+                        "jsonl" => Format::JsonLines,
+                        other => return Err(format!("unknown --format {other}")),
+                    };
+                }
+                "--output" => parsed.output = Some(value()?.into()),
+                _ => return Err(format!("unknown argument {arg}")),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(parse(&[]), Ok(Args::default()));
+    }
+
+    #[test]
+    fn all_options() {
+        let args = parse(&[
+            "--seed",
+            "42",
+            "--generations",
+            "7",
+            "--config",
+            "exp.toml",
+            "--format",
+            "jsonl",
+            "--output",
+            "stats.jsonl",
+        ]);
+
+        assert_eq!(
+            args,
+            Ok(Args {
+                seed: 42,
+                generations: 7,
+                config: Some("exp.toml".into()),
+                format: Format::JsonLines,
+                output: Some("stats.jsonl".into()),
+                help: false,
+            })
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "-1"]).is_err());
+        assert!(parse(&["--format", "xml"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+}
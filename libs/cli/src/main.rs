@@ -0,0 +1,195 @@
+//! Headless runner for the simulation, meant for long
+//! experiments on machines without a browser.
+mod args;
+
+use self::args::*;
+use lib_simulation as sim;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    if args.help {
+        println!("{USAGE}");
+        return;
+    }
+
+    #[cfg(feature = "tracing")]
+    init_tracing();
+
+    if let Err(err) = run(&args) {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let config = match &args.config {
+        Some(path) => load_config(path)?,
+        None => sim::Config::default(),
+    };
+
+    let output: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut output = BufWriter::new(output);
+
+    let mut rng = ChaCha8Rng::seed_from_u64(args.seed);
+    let mut simulation = sim::Simulation::random(config, &mut rng);
+
+    if args.format == Format::Csv {
+        let percentiles: String = simulation
+            .config()
+            .ga_percentiles
+            .iter()
+            .map(|p| format!(",p{p}_fitness"))
+            .collect();
+
+        writeln!(
+            output,
+            "generation,min_fitness,max_fitness,avg_fitness,median_fitness,\
+             std_dev_fitness{percentiles},best_index,diversity,species,mut_chance,mut_coeff,\
+             predator_avg_fitness,predator_max_fitness"
+        )?;
+    }
+
+    let started = Instant::now();
+
+    for generation in 0..args.generations {
+        let sim::Statistics {
+            birds: stats,
+            predators,
+        } = simulation.train(&mut rng);
+
+        let row = Row {
+            generation,
+            min_fitness: stats.min_fitness,
+            max_fitness: stats.max_fitness,
+            avg_fitness: stats.avg_fitness,
+            median_fitness: stats.median_fitness,
+            std_dev_fitness: stats.std_dev_fitness,
+            percentiles: stats
+                .percentiles
+                .iter()
+                .map(|p| (p.percentile, p.fitness))
+                .collect(),
+            best_index: stats.best_index,
+            diversity: stats.diversity,
+            species: stats.species.len(),
+            mut_chance: stats.mutation.map(|m| m.probability),
+            mut_coeff: stats.mutation.map(|m| m.magnitude),
+            predator_avg_fitness: predators.as_ref().map(|p| p.avg_fitness),
+            predator_max_fitness: predators.as_ref().map(|p| p.max_fitness),
+        };
+
+        match args.format {
+            Format::Csv => {
+                let percentiles: String = row
+                    .percentiles
+                    .iter()
+                    .map(|(_, fitness)| format!(",{fitness}"))
+                    .collect();
+
+                let optional =
+                    |value: Option<f32>| value.map(|v| v.to_string()).unwrap_or_default();
+
+                writeln!(
+                    output,
+                    "{},{},{},{},{},{}{},{},{},{},{},{},{},{}",
+                    row.generation,
+                    row.min_fitness,
+                    row.max_fitness,
+                    row.avg_fitness,
+                    row.median_fitness,
+                    row.std_dev_fitness,
+                    percentiles,
+                    row.best_index,
+                    row.diversity,
+                    row.species,
+                    optional(row.mut_chance),
+                    optional(row.mut_coeff),
+                    optional(row.predator_avg_fitness),
+                    optional(row.predator_max_fitness),
+                )?
+            }
+            Format::JsonLines => writeln!(output, "{}", serde_json::to_string(&row)?)?,
+        }
+    }
+
+    output.flush()?;
+
+    let elapsed = started.elapsed();
+
+    eprintln!(
+        "trained {} generations in {:.2?} ({:.2?} per generation)",
+        args.generations,
+        elapsed,
+        elapsed / args.generations.max(1) as u32,
+    );
+
+    Ok(())
+}
+
+/// A single line of output
+#[derive(Serialize)]
+struct Row {
+    generation: usize,
+    min_fitness: f32,
+    max_fitness: f32,
+    avg_fitness: f32,
+    median_fitness: f32,
+    std_dev_fitness: f32,
+    /// (percentile, fitness) pairs
+    percentiles: Vec<(f32, f32)>,
+    best_index: usize,
+    diversity: f32,
+    /// Number of species, 0 without speciation
+    species: usize,
+    /// Absent for mutation methods without these parameters,
+    /// or while islands have adapted them differently
+    mut_chance: Option<f32>,
+    mut_coeff: Option<f32>,
+    /// Absent without predators
+    predator_avg_fitness: Option<f32>,
+    predator_max_fitness: Option<f32>,
+}
+
+/// RUST_LOG=trace shows every animal's vision and brain output,
+/// RUST_LOG=info just the evolution of each generation
+#[cfg(feature = "tracing")]
+fn init_tracing() {
+    let level = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(tracing_subscriber::filter::LevelFilter::INFO);
+
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_max_level(level)
+        .init();
+}
+
+fn load_config(path: &Path) -> Result<sim::Config, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+
+    let config = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => sim::Config::from_json(&contents)?,
+        _ => sim::Config::from_toml(&contents)?,
+    };
+
+    Ok(config)
+}
@@ -0,0 +1,69 @@
+use crate::*;
+
+/// Range every gene has to stay within, used by operators
+/// that would otherwise push genes arbitrarily far away
+#[derive(Clone, Debug, PartialEq)]
+pub enum GeneBounds {
+    /// The same `(min, max)` for every gene
+    Uniform(f32, f32),
+    /// A separate `(min, max)` for each gene, in order
+    PerGene(Vec<(f32, f32)>),
+}
+
+impl GeneBounds {
+    /// `(min, max)` of the gene at `index`
+    pub fn get(&self, index: usize) -> (f32, f32) {
+        match self {
+            Self::Uniform(min, max) => (*min, *max),
+            Self::PerGene(bounds) => bounds[index],
+        }
+    }
+
+    pub fn clamp(&self, chromosome: &mut Chromosome) {
+        for (index, gene) in chromosome.iter_mut().enumerate() {
+            let (min, max) = self.get(index);
+
+            *gene = gene.clamp(min, max);
+        }
+    }
+
+    pub(crate) fn validate(&self) {
+        let valid = match self {
+            Self::Uniform(min, max) => min <= max,
+            Self::PerGene(bounds) => bounds.iter().all(|(min, max)| min <= max),
+        };
+
+        if !valid {
+            panic!("Lower bound can't exceed upper bound")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_uniform() {
+        let mut chromosome = vec![-2., 0.5, 2.].into_iter().collect();
+
+        GeneBounds::Uniform(-1., 1.).clamp(&mut chromosome);
+
+        assert_eq!(chromosome, vec![-1., 0.5, 1.].into_iter().collect());
+    }
+
+    #[test]
+    fn clamp_per_gene() {
+        let mut chromosome = vec![-2., 0.5, 2.].into_iter().collect();
+
+        GeneBounds::PerGene(vec![(0., 1.), (0., 0.25), (-5., 5.)]).clamp(&mut chromosome);
+
+        assert_eq!(chromosome, vec![0., 0.25, 2.].into_iter().collect());
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_inverted_bounds() {
+        GeneBounds::Uniform(1., -1.).validate();
+    }
+}
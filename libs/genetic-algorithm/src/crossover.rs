@@ -0,0 +1,637 @@
+use crate::*;
+use rand::seq::index;
+
+pub struct UniformCrossover;
+
+impl CrossoverMethod for UniformCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| if rng.gen_bool(0.5) { a } else { b })
+            .collect()
+    }
+}
+
+/// Cuts both parents at one random point, taking the
+/// genes before it from `parent_a` and the rest from `parent_b`
+pub struct SinglePointCrossover;
+
+impl CrossoverMethod for SinglePointCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        k_point(rng, parent_a, parent_b, 1)
+    }
+}
+
+/// Swaps the segment between two random points
+/// of `parent_a` for the one from `parent_b`
+pub struct TwoPointCrossover;
+
+impl CrossoverMethod for TwoPointCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        k_point(rng, parent_a, parent_b, 2)
+    }
+}
+
+/// Cuts both parents at `k` distinct random points and
+/// alternates between them, starting with `parent_a`.
+/// Chromosomes with fewer than `k + 1` genes are cut
+/// between every gene.
+#[derive(Clone, Debug)]
+pub struct KPointCrossover {
+    k: usize,
+}
+
+impl KPointCrossover {
+    pub fn new(k: usize) -> Self {
+        if k == 0 {
+            panic!("K-point crossover needs at least one point")
+        }
+
+        Self { k }
+    }
+}
+
+impl CrossoverMethod for KPointCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        k_point(rng, parent_a, parent_b, self.k)
+    }
+}
+
+fn k_point(
+    rng: &mut dyn RngCore,
+    parent_a: &Chromosome,
+    parent_b: &Chromosome,
+    k: usize,
+) -> Chromosome {
+    assert_eq!(parent_a.len(), parent_b.len());
+
+    // A cut at `n` falls between genes `n - 1` and `n`
+    let gaps = parent_a.len().saturating_sub(1);
+    let mut cuts: Vec<usize> = index::sample(rng, gaps, k.min(gaps))
+        .into_iter()
+        .map(|gap| gap + 1)
+        .collect();
+    cuts.sort_unstable();
+
+    let mut cuts = cuts.into_iter().peekable();
+    let mut from_a = true;
+
+    parent_a
+        .iter()
+        .zip(parent_b.iter())
+        .enumerate()
+        .map(|(i, (&a, &b))| {
+            if cuts.next_if_eq(&i).is_some() {
+                from_a = !from_a;
+            }
+
+            if from_a { a } else { b }
+        })
+        .collect()
+}
+
+/// Whole arithmetic recombination: every gene of the child
+/// is `alpha * a + (1 - alpha) * b`
+#[derive(Clone, Debug)]
+pub struct ArithmeticCrossover {
+    /// Weight of `parent_a`
+    /// - 0.5 = the average of both parents
+    /// - 1.0 = a copy of `parent_a`
+    alpha: f32,
+}
+
+impl ArithmeticCrossover {
+    pub fn new(alpha: f32) -> Self {
+        if !(0. ..=1.).contains(&alpha) {
+            panic!("Alpha is on b/w 0 & 1")
+        };
+
+        Self { alpha }
+    }
+}
+
+impl CrossoverMethod for ArithmeticCrossover {
+    fn crossover(
+        &self,
+        _rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| self.alpha * a + (1. - self.alpha) * b)
+            .collect()
+    }
+}
+
+/// BLX-alpha: every gene of the child is drawn uniformly from
+/// the range spanned by both parents' genes, widened on each
+/// side by `alpha` times its length, so children can explore
+/// a bit beyond their parents.
+#[derive(Clone, Debug)]
+pub struct BlendCrossover {
+    /// - 0.0 = stay strictly between the parents
+    /// - 0.5 = the usual choice, keeps the spread of genes steady
+    alpha: f32,
+}
+
+impl BlendCrossover {
+    pub fn new(alpha: f32) -> Self {
+        if alpha < 0. {
+            panic!("Alpha can't be negative")
+        };
+
+        Self { alpha }
+    }
+}
+
+impl CrossoverMethod for BlendCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .map(|(&a, &b)| {
+                let (min, max) = (a.min(b), a.max(b));
+                let extent = self.alpha * (max - min);
+
+                if min == max {
+                    min
+                } else {
+                    rng.gen_range((min - extent)..=(max + extent))
+                }
+            })
+            .collect()
+    }
+}
+
+/// Simulated Binary Crossover (SBX): spreads a child around its
+/// parents with the same probabilities single-point crossover
+/// has for binary strings, which suits real-valued genes
+#[derive(Clone, Debug)]
+pub struct SimulatedBinaryCrossover {
+    /// Distribution index
+    /// - ~2 = children land far from their parents
+    /// - ~20 = children land close to their parents
+    eta: f32,
+    bounds: Option<GeneBounds>,
+}
+
+impl SimulatedBinaryCrossover {
+    pub fn new(eta: f32) -> Self {
+        if eta < 0. {
+            panic!("Distribution index can't be negative")
+        };
+
+        Self { eta, bounds: None }
+    }
+
+    /// Keeps children within `bounds`, shrinking the spread
+    /// of genes close to them instead of just clamping
+    pub fn with_bounds(mut self, bounds: GeneBounds) -> Self {
+        bounds.validate();
+        self.bounds = Some(bounds);
+        self
+    }
+}
+
+impl CrossoverMethod for SimulatedBinaryCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+
+        let exponent = 1. / (self.eta + 1.);
+
+        parent_a
+            .iter()
+            .zip(parent_b.iter())
+            .enumerate()
+            .map(|(index, (&a, &b))| {
+                if a == b {
+                    return a;
+                }
+
+                let (min, max) = match &self.bounds {
+                    Some(bounds) => bounds.get(index),
+                    None => (f32::NEG_INFINITY, f32::INFINITY),
+                };
+
+                let (y1, y2) = (a.min(b), a.max(b));
+                let u: f32 = rng.r#gen();
+
+                // Without bounds `beta` is infinite and this turns
+                // into the textbook, unbounded SBX
+                let spread = |beta: f32| {
+                    let alpha = 2. - libm::powf(beta, -(self.eta + 1.));
+
+                    if u <= 1. / alpha {
+                        libm::powf(u * alpha, exponent)
+                    } else {
+                        libm::powf(1. / (2. - u * alpha), exponent)
+                    }
+                };
+
+                let child = if rng.gen_bool(0.5) {
+                    let beta = 1. + 2. * (y1 - min) / (y2 - y1);
+                    0.5 * ((y1 + y2) - spread(beta) * (y2 - y1))
+                } else {
+                    let beta = 1. + 2. * (max - y2) / (y2 - y1);
+                    0.5 * ((y1 + y2) + spread(beta) * (y2 - y1))
+                };
+
+                child.clamp(min, max)
+            })
+            .collect()
+    }
+}
+
+/// Structure-aware crossover for chromosomes of neural networks,
+/// laid out as `[bias, w0..wn]` per neuron, layer after layer.
+/// Instead of mixing single genes, it picks whole neurons (or
+/// whole layers) from either parent, so a child never ends up
+/// with a bias from one parent and weights from the other.
+#[derive(Clone, Debug)]
+pub struct NeuronCrossover {
+    /// Neurons per layer, starting with the input layer
+    layers: Vec<usize>,
+    unit: CrossoverUnit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrossoverUnit {
+    /// Every neuron comes from a randomly picked parent
+    Neuron,
+    /// Every layer comes as a whole from a randomly picked parent
+    Layer,
+}
+
+impl NeuronCrossover {
+    /// `layers` holds the number of neurons in each layer,
+    /// starting with the input layer - e.g. the `neurons`
+    /// of a network's `LayerTopology`
+    pub fn new(layers: impl IntoIterator<Item = usize>) -> Self {
+        let layers: Vec<usize> = layers.into_iter().collect();
+
+        if layers.len() < 2 {
+            panic!("Network needs at least two layers")
+        }
+
+        Self {
+            layers,
+            unit: CrossoverUnit::Neuron,
+        }
+    }
+
+    pub fn with_unit(mut self, unit: CrossoverUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Number of genes a chromosome of this network has
+    pub fn genes(&self) -> usize {
+        self.layers
+            .windows(2)
+            .map(|layers| layers[1] * (1 + layers[0]))
+            .sum()
+    }
+}
+
+impl CrossoverMethod for NeuronCrossover {
+    fn crossover(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &Chromosome,
+        parent_b: &Chromosome,
+    ) -> Chromosome {
+        assert_eq!(parent_a.len(), parent_b.len());
+        assert_eq!(parent_a.len(), self.genes());
+
+        let mut genes = Vec::with_capacity(parent_a.len());
+
+        for layers in self.layers.windows(2) {
+            // Every neuron has a bias and one weight per input
+            let neuron_len = 1 + layers[0];
+            let layer_len = layers[1] * neuron_len;
+
+            let block_len = match self.unit {
+                CrossoverUnit::Neuron => neuron_len,
+                CrossoverUnit::Layer => layer_len,
+            };
+
+            let start = genes.len();
+
+            for block in (start..start + layer_len).step_by(block_len) {
+                let parent = if rng.gen_bool(0.5) {
+                    parent_a
+                } else {
+                    parent_b
+                };
+
+                genes.extend_from_slice(&parent.genes[block..block + block_len]);
+            }
+        }
+
+        genes.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn parents() -> (Chromosome, Chromosome) {
+        let parent_a = (1..100).map(|n| n as f32).collect();
+        let parent_b = (1..100).map(|n| -n as f32).collect();
+
+        (parent_a, parent_b)
+    }
+
+    fn crossover(method: impl CrossoverMethod) -> Chromosome {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (parent_a, parent_b) = parents();
+
+        method.crossover(&mut rng, &parent_a, &parent_b)
+    }
+
+    /// Positions where the child switches from one parent to the other
+    fn switches(child: &Chromosome) -> Vec<usize> {
+        (1..child.len())
+            .filter(|&i| child[i - 1].signum() != child[i].signum())
+            .collect()
+    }
+
+    #[test]
+    fn uniform_crossover() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let parent_a = (1..100).map(|n| n as f32).collect();
+        let parent_b = (1..100).map(|n| -n as f32).collect();
+
+        let child = UniformCrossover.crossover(&mut rng, &parent_a, &parent_b);
+
+        let diff_a = child.iter().zip(parent_a).filter(|(c, p)| *c != p).count();
+        let diff_b = child.iter().zip(parent_b).filter(|(c, p)| *c != p).count();
+
+        assert_eq!(diff_a, 49);
+        assert_eq!(diff_b, 50);
+    }
+
+    #[test]
+    fn single_point_crossover() {
+        let child = crossover(SinglePointCrossover);
+
+        assert_eq!(switches(&child), vec![19]);
+        assert!(child[0] > 0.);
+    }
+
+    #[test]
+    fn two_point_crossover() {
+        let child = crossover(TwoPointCrossover);
+
+        assert_eq!(switches(&child), vec![19, 83]);
+        assert!(child[0] > 0. && child[98] > 0.);
+    }
+
+    #[test]
+    fn k_point_crossover() {
+        let child = crossover(KPointCrossover::new(5));
+
+        assert_eq!(switches(&child), vec![18, 62, 75, 80, 88]);
+    }
+
+    #[test]
+    fn k_point_crossover_with_more_points_than_genes() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let parent_a = vec![1., 2., 3.].into_iter().collect();
+        let parent_b = vec![-1., -2., -3.].into_iter().collect();
+
+        let child = KPointCrossover::new(10).crossover(&mut rng, &parent_a, &parent_b);
+
+        assert_eq!(child, vec![1., -2., 3.].into_iter().collect());
+    }
+
+    #[test]
+    fn arithmetic_crossover() {
+        let child = crossover(ArithmeticCrossover::new(0.75));
+
+        // 0.75 * n + 0.25 * -n
+        let expected: Vec<f32> = (1..100).map(|n| n as f32 * 0.5).collect();
+        let actual: Vec<f32> = child.into_iter().collect();
+
+        assert_relative_eq!(actual.as_slice(), expected.as_slice());
+    }
+
+    mod blend_crossover {
+        use super::*;
+
+        #[test]
+        fn zero_alpha_stays_between_parents() {
+            let child = crossover(BlendCrossover::new(0.));
+            let (parent_a, _) = parents();
+
+            assert!(
+                child
+                    .iter()
+                    .zip(parent_a.iter())
+                    .all(|(c, a)| c.abs() <= *a)
+            );
+        }
+
+        #[test]
+        fn alpha_explores_beyond_parents() {
+            let child = crossover(BlendCrossover::new(0.5));
+            let (parent_a, _) = parents();
+
+            let outside = child
+                .iter()
+                .zip(parent_a.iter())
+                .filter(|(c, a)| c.abs() > **a)
+                .count();
+
+            assert_eq!(outside, 40);
+            assert!(
+                child
+                    .iter()
+                    .zip(parent_a.iter())
+                    .all(|(c, a)| c.abs() <= 2. * a)
+            );
+        }
+
+        #[test]
+        fn identical_genes_are_kept() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let parent = vec![1., 2., 3.].into_iter().collect();
+
+            let child = BlendCrossover::new(0.5).crossover(&mut rng, &parent, &parent);
+
+            assert_eq!(child, parent);
+        }
+    }
+
+    mod simulated_binary_crossover {
+        use super::*;
+
+        /// Mean distance of the child's genes from the closer parent
+        fn distance(method: SimulatedBinaryCrossover) -> f32 {
+            let child = crossover(method);
+            let (parent_a, _) = parents();
+
+            child
+                .iter()
+                .zip(parent_a.iter())
+                .map(|(c, a)| (c.abs() - a).abs())
+                .sum::<f32>()
+                / child.len() as f32
+        }
+
+        #[test]
+        fn sample() {
+            let child = crossover(SimulatedBinaryCrossover::new(2.));
+            let genes: Vec<_> = child.iter().take(5).cloned().collect();
+
+            assert_relative_eq!(
+                genes.as_slice(),
+                [0.72079206, -2.2140012, -3.0727615, -3.1983404, 5.5669327].as_slice()
+            );
+        }
+
+        #[test]
+        fn higher_eta_stays_closer_to_parents() {
+            assert!(
+                distance(SimulatedBinaryCrossover::new(20.))
+                    < distance(SimulatedBinaryCrossover::new(0.5))
+            );
+        }
+
+        #[test]
+        fn stays_within_bounds() {
+            let bounds = GeneBounds::Uniform(-50., 50.);
+            let child = crossover(SimulatedBinaryCrossover::new(0.).with_bounds(bounds));
+
+            assert!(child.iter().all(|gene| (-50. ..=50.).contains(gene)));
+        }
+
+        #[test]
+        fn identical_genes_are_kept() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let parent = vec![1., 2., 3.].into_iter().collect();
+
+            let child = SimulatedBinaryCrossover::new(2.).crossover(&mut rng, &parent, &parent);
+
+            assert_eq!(child, parent);
+        }
+    }
+
+    mod neuron_crossover {
+        use super::*;
+
+        // 2 inputs -> 3 hidden -> 1 output, so 3 * 3 + 1 * 4 = 13 genes
+        const LAYERS: [usize; 3] = [2, 3, 1];
+
+        fn crossover(unit: CrossoverUnit) -> Chromosome {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let parent_a = (1..=13).map(|n| n as f32).collect();
+            let parent_b = (1..=13).map(|n| -n as f32).collect();
+
+            NeuronCrossover::new(LAYERS)
+                .with_unit(unit)
+                .crossover(&mut rng, &parent_a, &parent_b)
+        }
+
+        /// Whether each block of genes came from `parent_a`,
+        /// asserting that no block mixes both parents
+        fn origins(child: &Chromosome, blocks: &[usize]) -> Vec<bool> {
+            let mut genes = child.iter();
+
+            blocks
+                .iter()
+                .map(|&len| {
+                    let block: Vec<_> = genes.by_ref().take(len).collect();
+                    let from_a = *block[0] > 0.;
+
+                    assert!(block.iter().all(|gene| (**gene > 0.) == from_a));
+                    from_a
+                })
+                .collect()
+        }
+
+        #[test]
+        fn genes() {
+            assert_eq!(NeuronCrossover::new(LAYERS).genes(), 13);
+        }
+
+        #[test]
+        fn keeps_neurons_together() {
+            let child = crossover(CrossoverUnit::Neuron);
+
+            assert_eq!(
+                origins(&child, &[3, 3, 3, 4]),
+                vec![false, false, true, true]
+            );
+        }
+
+        #[test]
+        fn keeps_layers_together() {
+            let child = crossover(CrossoverUnit::Layer);
+
+            assert_eq!(origins(&child, &[9, 4]), vec![false, false]);
+        }
+
+        #[test]
+        fn keeps_genes_in_place() {
+            let child = crossover(CrossoverUnit::Neuron);
+
+            for (i, gene) in child.iter().enumerate() {
+                assert_eq!(gene.abs(), (i + 1) as f32);
+            }
+        }
+
+        #[test]
+        #[should_panic]
+        fn rejects_chromosomes_of_another_network() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let parent: Chromosome = (1..=12).map(|n| n as f32).collect();
+
+            NeuronCrossover::new(LAYERS).crossover(&mut rng, &parent, &parent);
+        }
+    }
+}
@@ -0,0 +1,432 @@
+use crate::*;
+use rand::seq::index;
+
+/// Island model: several populations evolve side by side, each
+/// with its own genetic algorithm, and every few generations
+/// exchange a couple of individuals. Islands drift towards
+/// different solutions, while migrants keep spreading the
+/// good ideas between them.
+pub struct Islands<S> {
+    algorithms: Vec<GeneticAlgorithm<S>>,
+    migration: Migration,
+    generation: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Migration {
+    topology: Topology,
+    /// Number of generations between migrations
+    interval: usize,
+    /// Number of individuals every island sends to each neighbour
+    count: usize,
+    selection: MigrantSelection,
+}
+
+/// Which islands send migrants to which
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology {
+    /// Every island to the next one, the last to the first
+    Ring,
+    /// Every island to every other one
+    FullyConnected,
+    /// Every island to another one picked at random each time
+    Random,
+}
+
+/// Which individuals of an island emigrate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrantSelection {
+    /// The fittest ones
+    Best,
+    /// Random ones, regardless of fitness
+    Random,
+}
+
+#[derive(Clone, Debug)]
+pub struct IslandStatistics {
+    /// All islands taken together as one population, with
+    /// `best_index` counting through the islands in order;
+    /// `mutation` is only set while every island agrees on it
+    pub overall: Statistics,
+    /// Each island's own, e.g. its adapted mutation parameters
+    pub islands: Vec<Statistics>,
+    /// Whether migrants were exchanged this generation
+    pub migrated: bool,
+}
+
+impl Migration {
+    pub fn new(topology: Topology, interval: usize, count: usize) -> Self {
+        if interval == 0 {
+            panic!("Migration interval must be at least one generation")
+        }
+
+        Self {
+            topology,
+            interval,
+            count,
+            selection: MigrantSelection::Best,
+        }
+    }
+
+    pub fn with_selection(mut self, selection: MigrantSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Islands that `island` sends migrants to
+    fn destinations(&self, rng: &mut dyn RngCore, island: usize, islands: usize) -> Vec<usize> {
+        if islands < 2 {
+            return Vec::new();
+        }
+
+        match self.topology {
+            Topology::Ring => vec![(island + 1) % islands],
+            Topology::FullyConnected => (0..islands).filter(|&other| other != island).collect(),
+            Topology::Random => {
+                // Any island but this one
+                let other = rng.gen_range(0..islands - 1);
+                vec![if other >= island { other + 1 } else { other }]
+            }
+        }
+    }
+
+    /// Copies of every island's emigrants, gathered by the
+    /// island they go to
+    pub fn arrivals<I>(&self, rng: &mut dyn RngCore, islands: &[Vec<I>]) -> Vec<Vec<Chromosome>>
+    where
+        I: Individual,
+    {
+        let mut arrivals: Vec<Vec<Chromosome>> = vec![Vec::new(); islands.len()];
+
+        for (island, population) in islands.iter().enumerate() {
+            let emigrants = self.emigrants(rng, population);
+
+            for destination in self.destinations(rng, island, islands.len()) {
+                arrivals[destination].extend(
+                    emigrants
+                        .iter()
+                        .map(|&index| population[index].chromosome().clone()),
+                );
+            }
+        }
+
+        arrivals
+    }
+
+    /// Indices of the individuals of `population` that emigrate
+    fn emigrants<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Vec<usize>
+    where
+        I: Individual,
+    {
+        let count = self.count.min(population.len());
+
+        match self.selection {
+            MigrantSelection::Best => {
+                let mut ranked: Vec<usize> = (0..population.len()).collect();
+                ranked
+                    .sort_by(|&a, &b| population[b].fitness().total_cmp(&population[a].fitness()));
+                ranked.truncate(count);
+                ranked
+            }
+            MigrantSelection::Random => index::sample(rng, population.len(), count).into_vec(),
+        }
+    }
+}
+
+impl<S> Islands<S>
+where
+    S: SelectionMethod,
+{
+    /// One island per genetic algorithm
+    pub fn new(algorithms: Vec<GeneticAlgorithm<S>>, migration: Migration) -> Self {
+        if algorithms.is_empty() {
+            panic!("Island model needs at least one island")
+        }
+
+        Self {
+            algorithms,
+            migration,
+            generation: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.algorithms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.algorithms.is_empty()
+    }
+
+    /// Evolves every island with its own genetic algorithm and,
+    /// when it's time, copies emigrants (picked from the evaluated
+    /// `islands`) over the last children of their destinations
+    pub fn evolve<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        islands: &[Vec<I>],
+    ) -> (Vec<Vec<I>>, IslandStatistics)
+    where
+        I: Individual,
+    {
+        assert_eq!(islands.len(), self.algorithms.len());
+
+        let (mut evolved, stats): (Vec<Vec<I>>, Vec<Statistics>) = self
+            .algorithms
+            .iter_mut()
+            .zip(islands)
+            .map(|(algorithm, population)| algorithm.evolve(rng, population))
+            .unzip();
+
+        self.generation += 1;
+
+        let migrated = self.generation.is_multiple_of(self.migration.interval) && islands.len() > 1;
+
+        if migrated {
+            let elites: Vec<usize> = stats.iter().map(|s| s.elite_fitness.len()).collect();
+            self.migrate(rng, islands, &mut evolved, &elites);
+        }
+
+        let mut overall = Statistics::from_individuals(
+            islands
+                .iter()
+                .flatten()
+                .map(|individual| (individual.fitness(), individual.chromosome())),
+            &self.algorithms[0].percentiles,
+        );
+
+        overall.elite_fitness = stats
+            .iter()
+            .flat_map(|stats| stats.elite_fitness.iter().copied())
+            .collect();
+        overall.elite_fitness.sort_by(|a, b| b.total_cmp(a));
+        // Islands adapt their mutation on their own, so
+        // there may be no single value to speak of
+        overall.mutation = stats[0]
+            .mutation
+            .filter(|mutation| stats.iter().all(|stats| stats.mutation == Some(*mutation)));
+        overall.species = stats
+            .iter()
+            .flat_map(|stats| stats.species.iter().cloned())
+            .collect();
+
+        let stats = IslandStatistics {
+            overall,
+            islands: stats,
+            migrated,
+        };
+
+        (evolved, stats)
+    }
+
+    /// Arrivals replace the last children of an island, never its
+    /// first `elites` (which come first), and any more of them than
+    /// there are children to replace are turned away
+    fn migrate<I>(
+        &self,
+        rng: &mut dyn RngCore,
+        islands: &[Vec<I>],
+        evolved: &mut [Vec<I>],
+        elites: &[usize],
+    ) where
+        I: Individual,
+    {
+        let arrivals = self.migration.arrivals(rng, islands);
+
+        for ((population, arrivals), &elites) in evolved.iter_mut().zip(arrivals).zip(elites) {
+            let start = population
+                .len()
+                .saturating_sub(arrivals.len())
+                .max(elites.min(population.len()));
+
+            for (slot, chromosome) in population[start..].iter_mut().zip(arrivals) {
+                *slot = I::create(chromosome);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestIndividual;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn individual(gene: f32) -> TestIndividual {
+        TestIndividual::create(vec![gene].into_iter().collect())
+    }
+
+    /// Island `n` holds individuals with genes `n * 10 + 0..4`
+    fn islands(count: usize) -> Vec<Vec<TestIndividual>> {
+        (0..count)
+            .map(|island| {
+                (0..4)
+                    .map(|n| individual((island * 10 + n) as f32))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn model(count: usize, migration: Migration) -> Islands<RouletteWheelSelection> {
+        let algorithms = (0..count)
+            .map(|_| {
+                // Never mutates, so children are clones of their parents
+                GeneticAlgorithm::new(
+                    RouletteWheelSelection,
+                    UniformCrossover,
+                    GaussianMutation::new(0., 0.),
+                )
+            })
+            .collect();
+
+        Islands::new(algorithms, migration)
+    }
+
+    fn genes(population: &[TestIndividual]) -> Vec<f32> {
+        population.iter().map(|i| i.chromosome()[0]).collect()
+    }
+
+    #[test]
+    fn ring() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = model(3, Migration::new(Topology::Ring, 1, 2));
+
+        let (evolved, stats) = model.evolve(&mut rng, &islands(3));
+
+        assert!(stats.migrated);
+
+        // The best two of the previous island replace the last two
+        assert_eq!(&genes(&evolved[0])[2..], [23., 22.]);
+        assert_eq!(&genes(&evolved[1])[2..], [3., 2.]);
+        assert_eq!(&genes(&evolved[2])[2..], [13., 12.]);
+    }
+
+    #[test]
+    fn fully_connected() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = model(3, Migration::new(Topology::FullyConnected, 1, 1));
+
+        let (evolved, _) = model.evolve(&mut rng, &islands(3));
+
+        assert_eq!(&genes(&evolved[0])[2..], [13., 23.]);
+        assert_eq!(&genes(&evolved[1])[2..], [3., 23.]);
+        assert_eq!(&genes(&evolved[2])[2..], [3., 13.]);
+    }
+
+    #[test]
+    fn elites_survive_migration() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let algorithms = (0..3)
+            .map(|_| {
+                GeneticAlgorithm::new(
+                    RouletteWheelSelection,
+                    UniformCrossover,
+                    GaussianMutation::new(0., 0.),
+                )
+                .with_elitism(Elitism::Count(3))
+            })
+            .collect();
+
+        // Four arrivals per island, for a single non-elite slot
+        let mut model = Islands::new(algorithms, Migration::new(Topology::FullyConnected, 1, 2));
+        let (evolved, _) = model.evolve(&mut rng, &islands(3));
+
+        assert_eq!(&genes(&evolved[0])[..3], [3., 2., 1.]);
+        assert_eq!(&genes(&evolved[1])[..3], [13., 12., 11.]);
+        assert_eq!(&genes(&evolved[2])[..3], [23., 22., 21.]);
+        assert_eq!(genes(&evolved[0])[3], 13.);
+    }
+
+    #[test]
+    fn random_never_migrates_to_itself() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let migration = Migration::new(Topology::Random, 1, 1);
+
+        for island in 0..3 {
+            for _ in 0..100 {
+                assert_ne!(migration.destinations(&mut rng, island, 3), vec![island]);
+            }
+        }
+    }
+
+    #[test]
+    fn random_migrants() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let migration =
+            Migration::new(Topology::Ring, 1, 2).with_selection(MigrantSelection::Random);
+
+        let emigrants = migration.emigrants(&mut rng, &islands(1)[0]);
+
+        assert_eq!(emigrants.len(), 2);
+        assert_ne!(emigrants[0], emigrants[1]);
+    }
+
+    #[test]
+    fn interval() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = model(2, Migration::new(Topology::Ring, 3, 1));
+        let mut population = islands(2);
+
+        let migrated: Vec<bool> = (0..6)
+            .map(|_| {
+                let (evolved, stats) = model.evolve(&mut rng, &population);
+                population = evolved;
+                stats.migrated
+            })
+            .collect();
+
+        assert_eq!(migrated, [false, false, true, false, false, true]);
+    }
+
+    #[test]
+    fn overall_statistics() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = model(2, Migration::new(Topology::Ring, 1, 1));
+
+        let (_, stats) = model.evolve(&mut rng, &islands(2));
+
+        assert_eq!(stats.islands.len(), 2);
+        assert_eq!(stats.overall.min_fitness, 0.);
+        assert_eq!(stats.overall.max_fitness, 13.);
+        assert_eq!(stats.overall.best_index, 7);
+    }
+
+    #[test]
+    fn overall_mutation_only_when_islands_agree() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let algorithm = |probability| {
+            GeneticAlgorithm::new(
+                RouletteWheelSelection,
+                UniformCrossover,
+                GaussianMutation::new(probability, 0.),
+            )
+        };
+
+        let migration = || Migration::new(Topology::Ring, 1, 1);
+
+        let mut agreeing = Islands::new(vec![algorithm(0.), algorithm(0.)], migration());
+        let (_, stats) = agreeing.evolve(&mut rng, &islands(2));
+
+        assert_eq!(stats.overall.mutation, stats.islands[0].mutation);
+        assert!(stats.overall.mutation.is_some());
+
+        let mut differing = Islands::new(vec![algorithm(0.), algorithm(0.5)], migration());
+        let (_, stats) = differing.evolve(&mut rng, &islands(2));
+
+        assert_eq!(stats.overall.mutation, None);
+        assert_eq!(stats.islands[1].mutation.unwrap().probability, 0.5);
+    }
+
+    #[test]
+    fn single_island_never_migrates() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut model = model(1, Migration::new(Topology::FullyConnected, 1, 2));
+
+        let (_, stats) = model.evolve(&mut rng, &islands(1));
+
+        assert!(!stats.migrated);
+    }
+}
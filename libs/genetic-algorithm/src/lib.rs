@@ -1,14 +1,61 @@
-use rand::prelude::SliceRandom;
 /// This is an implementation of a genetic algorithm in Rust.
 /// Genetic algorithm is one that estimates and clocks current
 /// solutions, and then improves them using the best of the bunch
-use rand::{Rng, RngCore};
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::ops::Index;
 
+mod bounds;
+mod crossover;
+mod islands;
+mod mutation;
+mod nsga2;
+mod selection;
+mod speciation;
+mod statistics;
+
+pub use self::{
+    bounds::*, crossover::*, islands::*, mutation::*, nsga2::*, selection::*, speciation::*,
+    statistics::*,
+};
+
 pub struct GeneticAlgorithm<S> {
     selection_method: S,
     crossover_method: Box<dyn CrossoverMethod>,
     mutation_method: Box<dyn MutationMethod>,
+    elitism: Elitism,
+    percentiles: Vec<f32>,
+    speciation: Option<Speciation>,
+}
+
+/// How many of the fittest individuals are copied
+/// into the next generation without crossover or mutation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Elitism {
+    /// A fixed number of individuals
+    Count(usize),
+    /// A fraction of the population in range 0..=1,
+    /// rounded to the nearest individual
+    Fraction(f32),
+}
+
+impl Elitism {
+    fn count(&self, population_len: usize) -> usize {
+        let count = match *self {
+            Self::Count(count) => count,
+            Self::Fraction(fraction) => (fraction * population_len as f32).round() as usize,
+        };
+
+        count.min(population_len)
+    }
+}
+
+impl Default for Elitism {
+    fn default() -> Self {
+        Self::Count(0)
+    }
 }
 
 impl<S> GeneticAlgorithm<S>
@@ -24,65 +71,172 @@ where
             selection_method,
             crossover_method: Box::new(crossover_method),
             mutation_method: Box::new(mutation_method),
+            elitism: Elitism::default(),
+            percentiles: Vec::new(),
+            speciation: None,
         }
     }
 
-    pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
+    pub fn with_elitism(mut self, elitism: Elitism) -> Self {
+        if let Elitism::Fraction(fraction) = elitism
+            && !(0. ..=1.).contains(&fraction)
+        {
+            panic!("Elite fraction is on b/w 0 & 1")
+        }
+
+        self.elitism = elitism;
+        self
+    }
+
+    /// Percentiles (in range 0..=100) of fitness to report
+    /// in `Statistics::percentiles`, besides the median
+    pub fn with_percentiles(mut self, percentiles: &[f32]) -> Self {
+        if !percentiles.iter().all(|p| (0. ..=100.).contains(p)) {
+            panic!("Percentiles are on b/w 0 & 100")
+        }
+
+        self.percentiles = percentiles.to_vec();
+        self
+    }
+
+    pub fn with_speciation(mut self, speciation: Speciation) -> Self {
+        self.speciation = Some(speciation);
+        self
+    }
+
+    /// Breeds the next generation and lets the mutation
+    /// method adapt to how this one did
+    pub fn evolve<I>(&mut self, rng: &mut dyn RngCore, population: &[I]) -> (Vec<I>, Statistics)
     where
         I: Individual,
     {
         assert!(!population.is_empty());
 
-        let new_pop = (0..population.len())
-            .map(|_| {
-                let parent_a = self.selection_method.select(rng, population).chromosome();
-                let parent_b = self.selection_method.select(rng, population).chromosome();
-                let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
+        let elites = Self::elites(population, self.elitism.count(population.len()));
 
-                self.mutation_method.mutate(rng, &mut child);
+        let count = population.len() - elites.len();
 
-                I::create(child)
+        let (parents, species) = match &self.speciation {
+            Some(speciation) => {
+                speciation.select_parents(&self.selection_method, rng, population, count)
+            }
+            None => (
+                self.selection_method
+                    .select_many(rng, population, 2 * count),
+                Vec::new(),
+            ),
+        };
+
+        // Every child gets its own RNG, seeded up front, so it
+        // comes out the same no matter which thread breeds it
+        let couples: Vec<_> = parents
+            .chunks_exact(2)
+            .map(|parents| {
+                (
+                    parents[0].chromosome(),
+                    parents[1].chromosome(),
+                    rng.next_u64(),
+                )
             })
             .collect();
 
-        let stats = Statistics::new(population);
+        let breed = |&(parent_a, parent_b, seed): &(&Chromosome, &Chromosome, u64)| {
+            breed(
+                &*self.crossover_method,
+                &*self.mutation_method,
+                &mut ChaCha8Rng::seed_from_u64(seed),
+                parent_a,
+                parent_b,
+            )
+        };
 
-        (new_pop, stats)
-    }
-}
+        #[cfg(feature = "parallel")]
+        let children: Vec<Chromosome> = couples.par_iter().map(breed).collect();
 
-pub struct RouletteWheelSelection;
+        #[cfg(not(feature = "parallel"))]
+        let children: Vec<Chromosome> = couples.iter().map(breed).collect();
 
-impl SelectionMethod for RouletteWheelSelection {
-    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
-    where
-        I: Individual,
-    {
-        let total_fitness: f32 = population
+        let new_pop = elites
             .iter()
-            .map(|individual| individual.fitness())
-            .sum();
+            .map(|elite| I::create(elite.chromosome().clone()))
+            .chain(children.into_iter().map(I::create))
+            .collect();
 
-        loop {
-            let indiv = population
-                .choose(rng)
-                .expect("population must not be empty");
+        let mut stats = Statistics::new(population, &self.percentiles);
+        stats.elite_fitness = elites.iter().map(|elite| elite.fitness()).collect();
+        stats.mutation = self.mutation_method.parameters();
+        stats.species = species;
 
-            let indiv_share = indiv.fitness() / total_fitness;
+        self.mutation_method.adapt(&stats);
 
-            if rng.gen_bool(indiv_share as f64) {
-                return indiv;
-            }
+        (new_pop, stats)
+    }
+
+    /// The `count` fittest individuals, best first
+    fn elites<I>(population: &[I], count: usize) -> Vec<&I>
+    where
+        I: Individual,
+    {
+        if count == 0 {
+            return Vec::new();
         }
+
+        let mut ranked: Vec<&I> = population.iter().collect();
+        ranked.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+        ranked.truncate(count);
+        ranked
     }
 }
 
+/// A single child of `parent_a` and `parent_b`, bred the same way
+/// as by `GeneticAlgorithm`, e.g. for evolving one at a time
+pub fn breed(
+    crossover_method: &dyn CrossoverMethod,
+    mutation_method: &dyn MutationMethod,
+    rng: &mut dyn RngCore,
+    parent_a: &Chromosome,
+    parent_b: &Chromosome,
+) -> Chromosome {
+    let mut child = crossover_method.crossover(rng, parent_a, parent_b);
+
+    child.inherit_step_sizes(parent_a, parent_b);
+    mutation_method.mutate(rng, &mut child);
+    child
+}
+
 #[derive(Clone, Debug)]
 pub struct Chromosome {
     genes: Vec<f32>,
+    /// Mutation strengths carried along by self-adaptive
+    /// mutation, empty for every other mutation method
+    step_sizes: Vec<f32>,
 }
 
 impl Chromosome {
+    pub fn with_step_sizes(mut self, step_sizes: Vec<f32>) -> Self {
+        self.step_sizes = step_sizes;
+        self
+    }
+
+    pub fn step_sizes(&self) -> &[f32] {
+        &self.step_sizes
+    }
+
+    /// Crossover methods only mix genes, so step sizes are
+    /// recombined separately, as the average of both parents
+    fn inherit_step_sizes(&mut self, parent_a: &Chromosome, parent_b: &Chromosome) {
+        self.step_sizes = if parent_a.step_sizes.len() == parent_b.step_sizes.len() {
+            parent_a
+                .step_sizes
+                .iter()
+                .zip(&parent_b.step_sizes)
+                .map(|(a, b)| (a + b) / 2.)
+                .collect()
+        } else {
+            parent_a.step_sizes.clone()
+        };
+    }
+
     pub fn len(&self) -> usize {
         self.genes.len()
     }
@@ -121,6 +275,7 @@ impl FromIterator<f32> for Chromosome {
     fn from_iter<T: IntoIterator<Item = f32>>(iter: T) -> Self {
         Self {
             genes: iter.into_iter().collect(),
+            step_sizes: Vec::new(),
         }
     }
 }
@@ -128,61 +283,7 @@ impl FromIterator<f32> for Chromosome {
 impl PartialEq for Chromosome {
     fn eq(&self, other: &Self) -> bool {
         approx::relative_eq!(self.genes.as_slice(), other.genes.as_slice())
-    }
-}
-
-pub struct UniformCrossover;
-
-impl CrossoverMethod for UniformCrossover {
-    fn crossover(
-        &self,
-        rng: &mut dyn RngCore,
-        parent_a: &Chromosome,
-        parent_b: &Chromosome,
-    ) -> Chromosome {
-        assert_eq!(parent_a.len(), parent_b.len());
-
-        parent_a
-            .iter()
-            .zip(parent_b.iter())
-            .map(|(&a, &b)| if rng.gen_bool(0.5) { a } else { b })
-            .collect()
-    }
-}
-
-#[derive(Clone, Debug)]
-pub struct GaussianMutation {
-    /// Probability of changing a gene
-    probability: f32,
-
-    /// Magnitude of the change
-    /// - 0.0 = genes will not be modified
-    /// - 3.0 = genes will be += or -= by at most 3.0
-    coefficient: f32,
-}
-
-impl GaussianMutation {
-    pub fn new(probability: f32, coefficient: f32) -> Self {
-        if !(0. ..=1.).contains(&probability) {
-            panic!("Probability is on b/w 0 & 1")
-        };
-
-        Self {
-            probability,
-            coefficient,
-        }
-    }
-}
-
-impl MutationMethod for GaussianMutation {
-    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
-        for gene in child.iter_mut() {
-            let sign = if rng.gen_bool(0.5) { -1. } else { 1. };
-
-            if rng.gen_bool(self.probability as f64) {
-                *gene += sign * self.coefficient * rng.r#gen::<f32>();
-            }
-        }
+            && approx::relative_eq!(self.step_sizes.as_slice(), other.step_sizes.as_slice())
     }
 }
 
@@ -197,9 +298,24 @@ pub trait SelectionMethod {
     fn select<'a, I>(&self, rn: &mut dyn RngCore, population: &'a [I]) -> &'a I
     where
         I: Individual;
+
+    /// Picks `count` individuals at once, for methods that
+    /// need the whole batch or can share work between picks
+    fn select_many<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Vec<&'a I>
+    where
+        I: Individual,
+    {
+        (0..count).map(|_| self.select(rng, population)).collect()
+    }
 }
 
-pub trait CrossoverMethod {
+/// `Send + Sync`, so that children can be bred on several threads
+pub trait CrossoverMethod: Send + Sync {
     fn crossover(
         &self,
         rng: &mut dyn RngCore,
@@ -208,54 +324,45 @@ pub trait CrossoverMethod {
     ) -> Chromosome;
 }
 
-pub trait MutationMethod {
+pub trait MutationMethod: Send + Sync {
     fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome);
-}
 
-#[derive(Clone, Debug)]
-pub struct Statistics {
-    pub min_fitness: f32,
-    pub max_fitness: f32,
-    pub avg_fitness: f32,
-}
+    /// Called after every generation, so the method can
+    /// tune itself based on how the population is doing
+    fn adapt(&mut self, _stats: &Statistics) {}
 
-impl Statistics {
-    fn new<I>(population: &[I]) -> Self
-    where
-        I: Individual,
-    {
-        assert!(!population.is_empty());
+    /// Current probability and magnitude, if the method has them
+    fn parameters(&self) -> Option<MutationParameters> {
+        None
+    }
+
+    fn set_parameters(&mut self, _parameters: MutationParameters) {}
+}
 
-        let mut min_fitness = population[0].fitness();
-        let mut max_fitness = min_fitness;
-        let mut sum_fitness = 0.0;
+impl MutationMethod for Box<dyn MutationMethod> {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        (**self).mutate(rng, child)
+    }
 
-        for i in population {
-            let fitness = i.fitness();
+    fn adapt(&mut self, stats: &Statistics) {
+        (**self).adapt(stats)
+    }
 
-            min_fitness = min_fitness.min(fitness);
-            max_fitness = max_fitness.max(fitness);
-            sum_fitness += fitness;
-        }
+    fn parameters(&self) -> Option<MutationParameters> {
+        (**self).parameters()
+    }
 
-        Self {
-            min_fitness,
-            max_fitness,
-            avg_fitness: sum_fitness / (population.len() as f32),
-        }
+    fn set_parameters(&mut self, parameters: MutationParameters) {
+        (**self).set_parameters(parameters)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rand::SeedableRng;
-    use rand_chacha::ChaCha8Rng;
-    use std::collections::BTreeMap;
-    use std::iter::FromIterator;
 
     #[derive(Clone, Debug, PartialEq)]
-    enum TestIndividual {
+    pub(crate) enum TestIndividual {
         /// For tests that require the chromosome
         WithChromosome { chromosome: Chromosome },
         /// For tests that only require the fitness
@@ -263,7 +370,7 @@ mod tests {
     }
 
     impl TestIndividual {
-        fn new(fitness: f32) -> Self {
+        pub(crate) fn new(fitness: f32) -> Self {
             Self::WithFitness { fitness }
         }
     }
@@ -291,175 +398,150 @@ mod tests {
     }
 
     #[test]
-    fn roulette_wheel_selection() {
-        let mut rng = ChaCha8Rng::from_seed(Default::default());
+    fn genetic_algorithm() {
+        fn individual(genes: &[f32]) -> TestIndividual {
+            TestIndividual::create(genes.iter().cloned().collect())
+        }
 
-        let population = vec![
-            TestIndividual::new(2.),
-            TestIndividual::new(1.),
-            TestIndividual::new(4.),
-            TestIndividual::new(3.),
-        ];
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
 
-        let mut actual_histogram = BTreeMap::new();
+        let mut gen_algo = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        );
 
-        for _ in 0..1000 {
-            let fitness = RouletteWheelSelection
-                .select(&mut rng, &population)
-                .fitness() as i32;
+        let mut population = vec![
+            individual(&[0., 0., 0.]),
+            individual(&[1., 1., 1.]),
+            individual(&[1., 2., 1.]),
+            individual(&[1., 2., 4.]),
+        ];
 
-            *actual_histogram.entry(fitness).or_insert(0) += 1;
+        for _ in 0..10 {
+            population = gen_algo.evolve(&mut rng, &population).0;
         }
 
-        let expected_histogram = BTreeMap::from_iter([(1, 97), (2, 208), (3, 302), (4, 393)]);
+        let expected_population = vec![
+            individual(&[-0.020123541, 1.04949, 3.9820611]),
+            individual(&[0.8112414, 2.3595643, 4.1070547]),
+            individual(&[0.5735407, 1.2417412, 4.096683]),
+            individual(&[0.22836185, 1.2417412, 4.074686]),
+        ];
 
-        assert_eq!(actual_histogram, expected_histogram);
+        assert_eq!(population, expected_population);
     }
 
-    #[test]
-    fn uniform_crossover() {
-        let mut rng = ChaCha8Rng::from_seed(Default::default());
-
-        let parent_a = (1..100).map(|n| n as f32).collect();
-        let parent_b = (1..100).map(|n| -n as f32).collect();
-
-        let child = UniformCrossover.crossover(&mut rng, &parent_a, &parent_b);
-
-        let diff_a = child
-            .iter()
-            .zip(parent_a.into_iter())
-            .filter(|(c, p)| *c != p)
-            .count();
-        let diff_b = child
-            .iter()
-            .zip(parent_b.into_iter())
-            .filter(|(c, p)| *c != p)
-            .count();
+    mod elitism {
+        use super::*;
 
-        assert_eq!(diff_a, 49);
-        assert_eq!(diff_b, 50);
-    }
+        fn individual(genes: &[f32]) -> TestIndividual {
+            TestIndividual::create(genes.iter().cloned().collect())
+        }
 
-    mod gaussian_mutation {
-        use super::*;
+        fn population() -> Vec<TestIndividual> {
+            vec![
+                individual(&[0., 0., 0.]),
+                individual(&[1., 2., 4.]),
+                individual(&[1., 1., 1.]),
+                individual(&[1., 2., 1.]),
+            ]
+        }
 
-        fn actual(probability: f32, coefficient: f32) -> Vec<f32> {
+        fn evolve(elitism: Elitism) -> (Vec<TestIndividual>, Statistics) {
             let mut rng = ChaCha8Rng::from_seed(Default::default());
-            let mut child = vec![1., 2., 3., 4., 5.].into_iter().collect();
-
-            GaussianMutation::new(probability, coefficient).mutate(&mut rng, &mut child);
 
-            child.into_iter().collect()
+            GeneticAlgorithm::new(
+                RouletteWheelSelection,
+                UniformCrossover,
+                GaussianMutation::new(1.0, 0.5),
+            )
+            .with_elitism(elitism)
+            .evolve(&mut rng, &population())
         }
-        mod given_zero_chance {
-            use approx::assert_relative_eq;
 
-            fn actual(coefficient: f32) -> Vec<f32> {
-                super::actual(0.0, coefficient)
-            }
+        #[test]
+        fn count_keeps_the_fittest_unchanged() {
+            let (population, stats) = evolve(Elitism::Count(2));
 
-            mod and_zero_coefficient {
-                use super::*;
+            assert_eq!(population.len(), 4);
+            assert_eq!(population[0], individual(&[1., 2., 4.]));
+            assert_eq!(population[1], individual(&[1., 2., 1.]));
+            assert_eq!(stats.elite_fitness, vec![7., 4.]);
+        }
 
-                #[test]
-                fn doesnt_change_original_chromosome() {
-                    let actual = actual(0.);
-                    let expected = vec![1., 2., 3., 4., 5.];
+        #[test]
+        fn fraction_is_rounded_to_individuals() {
+            let (population, stats) = evolve(Elitism::Fraction(0.3));
 
-                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
-                }
-            }
-            mod and_non_zero_coefficient {
-                use super::*;
+            assert_eq!(population[0], individual(&[1., 2., 4.]));
+            assert_ne!(population[1], individual(&[1., 2., 1.]));
+            assert_eq!(stats.elite_fitness, vec![7.]);
+        }
 
-                #[test]
-                fn doesnt_change_original_chromosome() {
-                    let actual = actual(0.5);
-                    let expected = vec![1., 2., 3., 4., 5.];
+        #[test]
+        fn count_is_capped_by_population() {
+            let (population, stats) = evolve(Elitism::Count(10));
 
-                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
-                }
-            }
+            assert_eq!(population.len(), 4);
+            assert_eq!(stats.elite_fitness, vec![7., 4., 3., 0.]);
         }
-        mod given_fifty_percent_probability {
-            use approx::assert_relative_eq;
 
-            fn actual(coefficient: f32) -> Vec<f32> {
-                super::actual(0.5, coefficient)
-            }
-
-            mod and_zero_coefficient {
-                use super::*;
+        #[test]
+        fn none_by_default() {
+            let (_, stats) = evolve(Elitism::default());
 
-                #[test]
-                fn doesnt_change_original_chromosome() {
-                    let actual = actual(0.);
-                    let expected = vec![1., 2., 3., 4., 5.];
+            assert!(stats.elite_fitness.is_empty());
+        }
 
-                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
-                }
-            }
-            mod and_non_zero_coefficient {
-                use super::*;
-                #[test]
-                fn slightly_changes_original_chromosome() {
-                    let actual = actual(0.5);
-                    let expected = vec![1.0, 1.7756249, 3.0, 4.1596804, 5.0];
-
-                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
-                }
-            }
+        #[test]
+        #[should_panic]
+        fn fraction_out_of_range() {
+            evolve(Elitism::Fraction(1.5));
         }
-        mod given_max_probability {
-            use approx::assert_relative_eq;
+    }
 
-            fn actual(coefficient: f32) -> Vec<f32> {
-                super::actual(1.0, coefficient)
-            }
-            mod and_zero_coefficient {
-                use super::*;
+    #[test]
+    fn genetic_algorithm_with_zero_fitness() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
 
-                #[test]
-                fn doesnt_change_original_chromosome() {
-                    let actual = actual(0.);
-                    let expected = vec![1., 2., 3., 4., 5.];
+        let mut gen_algo = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover,
+            GaussianMutation::new(0.5, 0.5),
+        );
 
-                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
-                }
-            }
-            mod and_non_zero_coefficient {
-                use super::*;
+        // Just like a freshly spawned world where nobody ate yet
+        let population: Vec<_> = (0..4)
+            .map(|_| TestIndividual::create(vec![0., 0., 0.].into_iter().collect()))
+            .collect();
 
-                #[test]
-                fn entirely_changes_original_chromosome() {
-                    let actual = actual(0.5);
-                    let expected = vec![1.4545316, 2.1162078, 2.7756248, 3.9505124, 4.638691];
+        let (population, stats) = gen_algo.evolve(&mut rng, &population);
 
-                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
-                }
-            }
-        }
+        assert_eq!(population.len(), 4);
+        assert_eq!(stats.max_fitness, 0.);
     }
 
     #[test]
-    fn genetic_algorithm() {
+    fn genetic_algorithm_with_tournament_selection() {
         fn individual(genes: &[f32]) -> TestIndividual {
             TestIndividual::create(genes.iter().cloned().collect())
         }
 
         let mut rng = ChaCha8Rng::from_seed(Default::default());
 
-        let gen_algo = GeneticAlgorithm::new(
-            RouletteWheelSelection,
+        let mut gen_algo = GeneticAlgorithm::new(
+            TournamentSelection::new(2, 0.9),
             UniformCrossover,
             GaussianMutation::new(0.5, 0.5),
         );
 
+        // Roulette wheel can't cope with these negative fitness values
         let mut population = vec![
-            individual(&[0., 0., 0.]),
-            individual(&[1., 1., 1.]),
-            individual(&[1., 2., 1.]),
-            individual(&[1., 2., 4.]),
+            individual(&[-4., -4., -4.]),
+            individual(&[-1., -1., -1.]),
+            individual(&[-1., -2., -1.]),
+            individual(&[1., -2., 4.]),
         ];
 
         for _ in 0..10 {
@@ -467,12 +549,78 @@ mod tests {
         }
 
         let expected_population = vec![
-            individual(&[1.0613362, 2.1978111, 4.3614755]),
-            individual(&[0.78856236, 1.4677399, 3.9275243]),
-            individual(&[0.78856236, 1.9645008, 3.8889477]),
-            individual(&[0.78856236, 2.080066, 3.6113224]),
+            individual(&[-0.26040983, -1.165885, 4.008948]),
+            individual(&[-0.46275184, -1.2692962, 3.315823]),
+            individual(&[-0.26040983, -0.7083527, 3.315823]),
+            individual(&[0.15304396, -0.83059055, 3.315823]),
         ];
 
         assert_eq!(population, expected_population);
     }
+
+    mod stochastic_universal_sampling {
+        use super::*;
+
+        fn population() -> Vec<TestIndividual> {
+            [[0., 0.], [0., 1.], [1., 0.], [10., 10.], [10., 11.]]
+                .iter()
+                .map(|genes| TestIndividual::create(genes.iter().cloned().collect()))
+                .collect()
+        }
+
+        fn gen_algo() -> GeneticAlgorithm<StochasticUniversalSampling> {
+            GeneticAlgorithm::new(
+                StochasticUniversalSampling,
+                UniformCrossover,
+                GaussianMutation::new(0.5, 0.5),
+            )
+        }
+
+        #[test]
+        fn with_everyone_elite() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let (evolved, stats) = gen_algo()
+                .with_elitism(Elitism::Count(5))
+                .evolve(&mut rng, &population());
+
+            assert_eq!(evolved.len(), 5);
+            assert_eq!(stats.elite_fitness.len(), 5);
+        }
+
+        #[test]
+        fn with_a_species_without_offspring() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            let (evolved, stats) = gen_algo()
+                .with_speciation(Speciation::new(2.))
+                .evolve(&mut rng, &population());
+
+            assert_eq!(evolved.len(), 5);
+            assert_eq!(stats.species[0].offspring, 0);
+        }
+    }
+
+    #[test]
+    fn step_sizes_are_inherited_as_average() {
+        let parent_a = vec![1., 2.]
+            .into_iter()
+            .collect::<Chromosome>()
+            .with_step_sizes(vec![0.2, 0.4]);
+        let parent_b = vec![3., 4.]
+            .into_iter()
+            .collect::<Chromosome>()
+            .with_step_sizes(vec![0.4, 0.8]);
+
+        let mut child: Chromosome = vec![1., 4.].into_iter().collect();
+        child.inherit_step_sizes(&parent_a, &parent_b);
+
+        assert_eq!(
+            child,
+            vec![1., 4.]
+                .into_iter()
+                .collect::<Chromosome>()
+                .with_step_sizes(vec![0.3, 0.6])
+        );
+    }
 }
@@ -0,0 +1,931 @@
+use crate::*;
+use std::f32::consts::TAU;
+
+/// Despite its name, changes genes by a uniformly distributed
+/// amount - see `NormalMutation` for normally distributed ones
+#[derive(Clone, Debug)]
+pub struct GaussianMutation {
+    /// Probability of changing a gene
+    probability: f32,
+
+    /// Magnitude of the change
+    /// - 0.0 = genes will not be modified
+    /// - 3.0 = genes will be += or -= by at most 3.0
+    coefficient: f32,
+}
+
+impl GaussianMutation {
+    pub fn new(probability: f32, coefficient: f32) -> Self {
+        if !(0. ..=1.).contains(&probability) {
+            panic!("Probability is on b/w 0 & 1")
+        };
+
+        Self {
+            probability,
+            coefficient,
+        }
+    }
+}
+
+impl MutationMethod for GaussianMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        for gene in child.iter_mut() {
+            let sign = if rng.gen_bool(0.5) { -1. } else { 1. };
+
+            if rng.gen_bool(self.probability as f64) {
+                *gene += sign * self.coefficient * rng.r#gen::<f32>();
+            }
+        }
+    }
+
+    fn parameters(&self) -> Option<MutationParameters> {
+        Some(MutationParameters {
+            probability: self.probability,
+            magnitude: self.coefficient,
+        })
+    }
+
+    fn set_parameters(&mut self, parameters: MutationParameters) {
+        self.probability = parameters.probability;
+        self.coefficient = parameters.magnitude;
+    }
+}
+
+/// Changes genes by a normally distributed amount
+#[derive(Clone, Debug)]
+pub struct NormalMutation {
+    /// Probability of changing a gene
+    probability: f32,
+
+    /// Standard deviation of the change
+    std_dev: f32,
+}
+
+impl NormalMutation {
+    pub fn new(probability: f32, std_dev: f32) -> Self {
+        if !(0. ..=1.).contains(&probability) {
+            panic!("Probability is on b/w 0 & 1")
+        };
+
+        if std_dev < 0. {
+            panic!("Standard deviation can't be negative")
+        };
+
+        Self {
+            probability,
+            std_dev,
+        }
+    }
+}
+
+impl MutationMethod for NormalMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        for gene in child.iter_mut() {
+            if rng.gen_bool(self.probability as f64) {
+                *gene += self.std_dev * standard_normal(rng);
+            }
+        }
+    }
+
+    fn parameters(&self) -> Option<MutationParameters> {
+        Some(MutationParameters {
+            probability: self.probability,
+            magnitude: self.std_dev,
+        })
+    }
+
+    fn set_parameters(&mut self, parameters: MutationParameters) {
+        self.probability = parameters.probability;
+        self.std_dev = parameters.magnitude;
+    }
+}
+
+/// A sample of the standard normal distribution, by Box-Muller
+/// through libm, so it's bit for bit the same on every target
+fn standard_normal(rng: &mut dyn RngCore) -> f32 {
+    // In 0..1 and 0..=1, so that the logarithm stays finite
+    let angle = TAU * rng.r#gen::<f32>();
+    let radius = libm::sqrtf(-2. * libm::logf(1. - rng.r#gen::<f32>()));
+
+    radius * libm::cosf(angle)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MutationParameters {
+    /// Probability of changing a gene
+    pub probability: f32,
+    /// How much a gene changes, e.g. `GaussianMutation`'s
+    /// coefficient or `NormalMutation`'s standard deviation
+    pub magnitude: f32,
+}
+
+/// Wraps a mutation method with probability and magnitude
+/// (e.g. `GaussianMutation`) and retunes them after every
+/// generation, instead of keeping them fixed
+#[derive(Clone, Debug)]
+pub struct AdaptiveMutation<M> {
+    inner: M,
+    rule: AdaptationRule,
+    /// Parameters the inner method started with
+    base: MutationParameters,
+    /// Most the magnitude can grow to, relative to `base`
+    max_boost: f32,
+    best_fitness: f32,
+    /// Recent generations, whether each improved `best_fitness`
+    history: Vec<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AdaptationRule {
+    /// Rechenberg's 1/5th success rule: every `window` generations,
+    /// multiplies magnitude by `factor` if more than a fifth of them
+    /// improved the best fitness and divides it by `factor` otherwise
+    OneFifthSuccess { window: usize, factor: f32 },
+    /// Multiplies probability and magnitude by `factor` every
+    /// generation diversity is below `threshold`, and eases them
+    /// back towards their base values once it recovers
+    Diversity { threshold: f32, factor: f32 },
+    /// Multiplies probability and magnitude by `factor` after
+    /// every `generations` generations without an improvement
+    /// of the best fitness, and resets them once it improves
+    Stagnation { generations: usize, factor: f32 },
+}
+
+impl<M> AdaptiveMutation<M>
+where
+    M: MutationMethod,
+{
+    pub fn new(inner: M, rule: AdaptationRule) -> Self {
+        let Some(base) = inner.parameters() else {
+            panic!("Mutation method has no parameters to adapt")
+        };
+
+        // Otherwise the magnitude's bounds would be upside down
+        if base.magnitude < 0. {
+            panic!("Magnitude to adapt can't be negative")
+        }
+
+        let (AdaptationRule::OneFifthSuccess { factor, .. }
+        | AdaptationRule::Diversity { factor, .. }
+        | AdaptationRule::Stagnation { factor, .. }) = rule;
+
+        if factor <= 1. {
+            panic!("Adaptation factor must exceed 1")
+        }
+
+        if let AdaptationRule::OneFifthSuccess { window: 0, .. }
+        | AdaptationRule::Stagnation { generations: 0, .. } = rule
+        {
+            panic!("Adaptation needs at least one generation")
+        }
+
+        Self {
+            inner,
+            rule,
+            base,
+            max_boost: 100.,
+            best_fitness: f32::NEG_INFINITY,
+            history: Vec::new(),
+        }
+    }
+
+    /// Keeps magnitude within `base / max_boost..=base * max_boost`
+    pub fn with_max_boost(mut self, max_boost: f32) -> Self {
+        if max_boost < 1. {
+            panic!("Max boost can't be less than 1")
+        }
+
+        self.max_boost = max_boost;
+        self
+    }
+
+    fn scale(&mut self, probability: f32, magnitude: f32) {
+        let current = self.inner.parameters().unwrap_or(self.base);
+
+        self.inner.set_parameters(MutationParameters {
+            probability: (current.probability * probability).clamp(0., 1.),
+            magnitude: (current.magnitude * magnitude).clamp(
+                self.base.magnitude / self.max_boost,
+                self.base.magnitude * self.max_boost,
+            ),
+        });
+    }
+}
+
+impl<M> MutationMethod for AdaptiveMutation<M>
+where
+    M: MutationMethod,
+{
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        self.inner.mutate(rng, child)
+    }
+
+    fn adapt(&mut self, stats: &Statistics) {
+        let improved = stats.max_fitness > self.best_fitness;
+
+        if improved {
+            self.best_fitness = stats.max_fitness;
+        }
+
+        self.history.push(improved);
+
+        match self.rule {
+            AdaptationRule::OneFifthSuccess { window, factor } => {
+                if self.history.len() < window {
+                    return;
+                }
+
+                let successes = self.history.iter().filter(|improved| **improved).count();
+                self.history.clear();
+
+                if successes * 5 > window {
+                    self.scale(1., factor);
+                } else {
+                    self.scale(1., 1. / factor);
+                }
+            }
+
+            AdaptationRule::Diversity { threshold, factor } => {
+                self.history.clear();
+
+                if stats.diversity < threshold {
+                    self.scale(factor, factor);
+                } else {
+                    let current = self.inner.parameters().unwrap_or(self.base);
+
+                    self.inner.set_parameters(MutationParameters {
+                        probability: (current.probability / factor).max(self.base.probability),
+                        magnitude: (current.magnitude / factor).max(self.base.magnitude),
+                    });
+                }
+            }
+
+            AdaptationRule::Stagnation {
+                generations,
+                factor,
+            } => {
+                if improved {
+                    self.history.clear();
+                    self.inner.set_parameters(self.base);
+                } else if self.history.len() >= generations {
+                    self.history.clear();
+                    self.scale(factor, factor);
+                }
+            }
+        }
+    }
+
+    fn parameters(&self) -> Option<MutationParameters> {
+        self.inner.parameters()
+    }
+
+    fn set_parameters(&mut self, parameters: MutationParameters) {
+        self.inner.set_parameters(parameters)
+    }
+}
+
+/// Evolution-strategy style mutation, where every chromosome
+/// carries its own step size(s). Step sizes are mutated
+/// log-normally first and then used as the standard deviation
+/// of a normally distributed change of every gene, so that
+/// mutation strength evolves along with the genes.
+#[derive(Clone, Debug)]
+pub struct SelfAdaptiveMutation {
+    /// Step size given to chromosomes that don't have one yet
+    initial_step_size: f32,
+    /// Keeps step sizes from collapsing to 0.0
+    min_step_size: f32,
+    step_sizes: StepSizes,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepSizes {
+    /// A single step size shared by all genes
+    One,
+    /// A separate step size for every gene
+    PerGene,
+}
+
+impl SelfAdaptiveMutation {
+    pub fn new(initial_step_size: f32) -> Self {
+        if initial_step_size <= 0. {
+            panic!("Step size must be positive")
+        };
+
+        Self {
+            initial_step_size,
+            min_step_size: 1e-5,
+            step_sizes: StepSizes::One,
+        }
+    }
+
+    pub fn with_step_sizes(mut self, step_sizes: StepSizes) -> Self {
+        self.step_sizes = step_sizes;
+        self
+    }
+
+    pub fn with_min_step_size(mut self, min_step_size: f32) -> Self {
+        if min_step_size < 0. {
+            panic!("Step size can't be negative")
+        };
+
+        self.min_step_size = min_step_size;
+        self
+    }
+}
+
+impl MutationMethod for SelfAdaptiveMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        let genes = child.len() as f32;
+
+        let count = match self.step_sizes {
+            StepSizes::One => 1,
+            StepSizes::PerGene => child.len(),
+        };
+
+        if child.step_sizes.len() != count {
+            child.step_sizes = vec![self.initial_step_size; count];
+        }
+
+        // Learning rates recommended by Schwefel
+        let (global_rate, local_rate) = match self.step_sizes {
+            StepSizes::One => (0., 1. / libm::sqrtf(genes)),
+            StepSizes::PerGene => (
+                1. / libm::sqrtf(2. * genes),
+                1. / libm::sqrtf(2. * libm::sqrtf(genes)),
+            ),
+        };
+
+        let global = global_rate * standard_normal(rng);
+
+        for step_size in &mut child.step_sizes {
+            let local = local_rate * standard_normal(rng);
+
+            *step_size = (*step_size * libm::expf(global + local)).max(self.min_step_size);
+        }
+
+        let Chromosome { genes, step_sizes } = child;
+
+        for (index, gene) in genes.iter_mut().enumerate() {
+            let step_size = step_sizes[index % step_sizes.len()];
+
+            *gene += step_size * standard_normal(rng);
+        }
+    }
+}
+
+/// Polynomial mutation: moves a gene by an amount drawn from
+/// a polynomial distribution, mostly by a little and rarely by
+/// a lot, as controlled by the distribution index `eta`
+#[derive(Clone, Debug)]
+pub struct PolynomialMutation {
+    /// Probability of changing a gene
+    probability: f32,
+
+    /// Distribution index
+    /// - ~5 = large changes are fairly common
+    /// - ~100 = genes barely move
+    eta: f32,
+
+    /// Without bounds genes move by at most 1.0 either way,
+    /// with bounds by at most the width of their range
+    bounds: Option<GeneBounds>,
+}
+
+impl PolynomialMutation {
+    pub fn new(probability: f32, eta: f32) -> Self {
+        if !(0. ..=1.).contains(&probability) {
+            panic!("Probability is on b/w 0 & 1")
+        };
+
+        if eta < 0. {
+            panic!("Distribution index can't be negative")
+        };
+
+        Self {
+            probability,
+            eta,
+            bounds: None,
+        }
+    }
+
+    pub fn with_bounds(mut self, bounds: GeneBounds) -> Self {
+        bounds.validate();
+        self.bounds = Some(bounds);
+        self
+    }
+}
+
+impl MutationMethod for PolynomialMutation {
+    fn mutate(&self, rng: &mut dyn RngCore, child: &mut Chromosome) {
+        let exponent = 1. / (self.eta + 1.);
+
+        for (index, gene) in child.iter_mut().enumerate() {
+            if !rng.gen_bool(self.probability as f64) {
+                continue;
+            }
+
+            // How close the gene is to its lower and upper bound,
+            // relative to the range - unbounded genes are "far"
+            let (range, to_min, to_max) = match &self.bounds {
+                Some(bounds) => {
+                    let (min, max) = bounds.get(index);
+                    let range = max - min;
+
+                    *gene = gene.clamp(min, max);
+
+                    if range == 0. {
+                        continue;
+                    }
+
+                    (range, (*gene - min) / range, (max - *gene) / range)
+                }
+                None => (1., 1., 1.),
+            };
+
+            let u: f32 = rng.r#gen();
+
+            let delta = if u < 0.5 {
+                let edge = libm::powf(1. - to_min, self.eta + 1.);
+                libm::powf(2. * u + (1. - 2. * u) * edge, exponent) - 1.
+            } else {
+                let edge = libm::powf(1. - to_max, self.eta + 1.);
+                1. - libm::powf(2. * (1. - u) + 2. * (u - 0.5) * edge, exponent)
+            };
+
+            *gene += delta * range;
+
+            if let Some(bounds) = &self.bounds {
+                let (min, max) = bounds.get(index);
+                *gene = gene.clamp(min, max);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    mod gaussian_mutation {
+        use super::*;
+
+        fn actual(probability: f32, coefficient: f32) -> Vec<f32> {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut child = vec![1., 2., 3., 4., 5.].into_iter().collect();
+
+            GaussianMutation::new(probability, coefficient).mutate(&mut rng, &mut child);
+
+            child.into_iter().collect()
+        }
+        mod given_zero_chance {
+            use approx::assert_relative_eq;
+
+            fn actual(coefficient: f32) -> Vec<f32> {
+                super::actual(0.0, coefficient)
+            }
+
+            mod and_zero_coefficient {
+                use super::*;
+
+                #[test]
+                fn doesnt_change_original_chromosome() {
+                    let actual = actual(0.);
+                    let expected = vec![1., 2., 3., 4., 5.];
+
+                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
+                }
+            }
+            mod and_non_zero_coefficient {
+                use super::*;
+
+                #[test]
+                fn doesnt_change_original_chromosome() {
+                    let actual = actual(0.5);
+                    let expected = vec![1., 2., 3., 4., 5.];
+
+                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
+                }
+            }
+        }
+        mod given_fifty_percent_probability {
+            use approx::assert_relative_eq;
+
+            fn actual(coefficient: f32) -> Vec<f32> {
+                super::actual(0.5, coefficient)
+            }
+
+            mod and_zero_coefficient {
+                use super::*;
+
+                #[test]
+                fn doesnt_change_original_chromosome() {
+                    let actual = actual(0.);
+                    let expected = vec![1., 2., 3., 4., 5.];
+
+                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
+                }
+            }
+            mod and_non_zero_coefficient {
+                use super::*;
+                #[test]
+                fn slightly_changes_original_chromosome() {
+                    let actual = actual(0.5);
+                    let expected = vec![1.0, 1.7756249, 3.0, 4.1596804, 5.0];
+
+                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
+                }
+            }
+        }
+        mod given_max_probability {
+            use approx::assert_relative_eq;
+
+            fn actual(coefficient: f32) -> Vec<f32> {
+                super::actual(1.0, coefficient)
+            }
+            mod and_zero_coefficient {
+                use super::*;
+
+                #[test]
+                fn doesnt_change_original_chromosome() {
+                    let actual = actual(0.);
+                    let expected = vec![1., 2., 3., 4., 5.];
+
+                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
+                }
+            }
+            mod and_non_zero_coefficient {
+                use super::*;
+
+                #[test]
+                fn entirely_changes_original_chromosome() {
+                    let actual = actual(0.5);
+                    let expected = vec![1.4545316, 2.1162078, 2.7756248, 3.9505124, 4.638691];
+
+                    assert_relative_eq!(actual.as_slice(), expected.as_slice());
+                }
+            }
+        }
+    }
+
+    mod polynomial_mutation {
+        use super::*;
+        use approx::assert_relative_eq;
+
+        fn actual(method: PolynomialMutation) -> Vec<f32> {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut child = vec![1., 2., 3., 4., 5.].into_iter().collect();
+
+            method.mutate(&mut rng, &mut child);
+            child.into_iter().collect()
+        }
+
+        #[test]
+        fn zero_chance_does_not_change_the_original_chromosome() {
+            let actual = actual(PolynomialMutation::new(0., 20.));
+
+            assert_relative_eq!(actual.as_slice(), [1., 2., 3., 4., 5.].as_slice());
+        }
+
+        #[test]
+        fn max_chance() {
+            let actual = actual(PolynomialMutation::new(1., 20.));
+
+            assert_relative_eq!(
+                actual.as_slice(),
+                [0.9543049, 2.0519526, 3.0779574, 4.014417, 5.0347204].as_slice()
+            );
+        }
+
+        #[test]
+        fn unbounded_genes_move_by_at_most_one() {
+            let actual = actual(PolynomialMutation::new(1., 0.));
+
+            for (before, after) in [1., 2., 3., 4., 5.].iter().zip(&actual) {
+                assert!((before - after).abs() <= 1.);
+            }
+        }
+
+        #[test]
+        fn stays_within_bounds() {
+            let bounds =
+                GeneBounds::PerGene(vec![(0., 1.), (1., 3.), (3., 3.), (0., 10.), (4., 6.)]);
+            let actual = actual(PolynomialMutation::new(1., 0.).with_bounds(bounds.clone()));
+
+            for (index, gene) in actual.iter().enumerate() {
+                let (min, max) = bounds.get(index);
+                assert!((min..=max).contains(gene));
+            }
+
+            assert_eq!(actual[2], 3.);
+        }
+    }
+
+    mod normal_mutation {
+        use super::*;
+        use approx::assert_relative_eq;
+
+        fn actual(probability: f32, std_dev: f32) -> Vec<f32> {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut child = vec![1., 2., 3., 4., 5.].into_iter().collect();
+
+            NormalMutation::new(probability, std_dev).mutate(&mut rng, &mut child);
+            child.into_iter().collect()
+        }
+
+        #[test]
+        fn zero_chance_does_not_change_the_original_chromosome() {
+            let actual = actual(0., 1.);
+
+            assert_relative_eq!(actual.as_slice(), [1., 2., 3., 4., 5.].as_slice());
+        }
+
+        #[test]
+        fn zero_std_dev_does_not_change_the_original_chromosome() {
+            let actual = actual(1., 0.);
+
+            assert_relative_eq!(actual.as_slice(), [1., 2., 3., 4., 5.].as_slice());
+        }
+
+        #[test]
+        fn max_chance() {
+            let actual = actual(1., 0.5);
+
+            assert_relative_eq!(
+                actual.as_slice(),
+                [1.3658389, 2.5942268, 3.0272577, 3.7558162, 4.6355724].as_slice()
+            );
+        }
+
+        #[test]
+        fn changes_are_normally_distributed() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut child: Chromosome = vec![0.; 10_000].into_iter().collect();
+
+            NormalMutation::new(1., 2.).mutate(&mut rng, &mut child);
+
+            let len = child.len() as f32;
+            let mean = child.iter().sum::<f32>() / len;
+            let std_dev = (child.iter().map(|g| (g - mean).powi(2)).sum::<f32>() / len).sqrt();
+            let within_one = child
+                .iter()
+                .filter(|g| (*g - mean).abs() <= std_dev)
+                .count();
+
+            assert_relative_eq!(mean, 0., epsilon = 0.05);
+            assert_relative_eq!(std_dev, 2., epsilon = 0.05);
+            // ~68% for a normal distribution, ~58% for a uniform one
+            assert_relative_eq!(within_one as f32 / len, 0.68, epsilon = 0.02);
+        }
+    }
+
+    mod self_adaptive_mutation {
+        use super::*;
+        use approx::assert_relative_eq;
+
+        fn mutate(method: &SelfAdaptiveMutation, child: &mut Chromosome) {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            method.mutate(&mut rng, child);
+        }
+
+        #[test]
+        fn one_step_size() {
+            let mut child: Chromosome = vec![1., 2., 3., 4.].into_iter().collect();
+
+            mutate(&SelfAdaptiveMutation::new(0.5), &mut child);
+
+            assert_relative_eq!(child.step_sizes(), [0.90581477].as_slice());
+            assert_relative_eq!(
+                child.genes.as_slice(),
+                [1.0493808, 1.5576292, 2.339792, 3.6599925].as_slice()
+            );
+        }
+
+        #[test]
+        fn per_gene_step_sizes() {
+            let mut child: Chromosome = vec![1., 2., 3., 4.].into_iter().collect();
+
+            let method = SelfAdaptiveMutation::new(0.5).with_step_sizes(StepSizes::PerGene);
+            mutate(&method, &mut child);
+
+            assert_relative_eq!(
+                child.step_sizes(),
+                [1.1732371, 0.6655095, 0.5073045, 0.44982898].as_slice()
+            );
+        }
+
+        #[test]
+        fn keeps_existing_step_sizes() {
+            let mut child = vec![1., 2.]
+                .into_iter()
+                .collect::<Chromosome>()
+                .with_step_sizes(vec![0.]);
+
+            mutate(
+                &SelfAdaptiveMutation::new(0.5).with_min_step_size(0.),
+                &mut child,
+            );
+
+            // A step size of 0.0 stays 0.0 and leaves genes alone
+            assert_eq!(
+                child,
+                vec![1., 2.]
+                    .into_iter()
+                    .collect::<Chromosome>()
+                    .with_step_sizes(vec![0.])
+            );
+        }
+
+        #[test]
+        fn min_step_size() {
+            let mut child = vec![1., 2.]
+                .into_iter()
+                .collect::<Chromosome>()
+                .with_step_sizes(vec![1e-9]);
+
+            mutate(
+                &SelfAdaptiveMutation::new(0.5).with_min_step_size(0.1),
+                &mut child,
+            );
+
+            assert!(child.step_sizes()[0] >= 0.1);
+        }
+    }
+
+    mod adaptive_mutation {
+        use super::*;
+        use crate::tests::TestIndividual;
+        use approx::assert_relative_eq;
+
+        fn stats(max_fitness: f32, diversity: f32) -> Statistics {
+            let population = [TestIndividual::create(
+                vec![max_fitness].into_iter().collect(),
+            )];
+            let mut stats = Statistics::new(&population, &[]);
+            stats.diversity = diversity;
+            stats
+        }
+
+        fn adapt(rule: AdaptationRule, generations: &[(f32, f32)]) -> MutationParameters {
+            let mut method = AdaptiveMutation::new(GaussianMutation::new(0.1, 1.), rule);
+
+            for &(max_fitness, diversity) in generations {
+                method.adapt(&stats(max_fitness, diversity));
+            }
+
+            method.parameters().unwrap()
+        }
+
+        fn assert_parameters(actual: MutationParameters, probability: f32, magnitude: f32) {
+            assert_relative_eq!(actual.probability, probability);
+            assert_relative_eq!(actual.magnitude, magnitude);
+        }
+
+        mod one_fifth_success {
+            use super::*;
+
+            const RULE: AdaptationRule = AdaptationRule::OneFifthSuccess {
+                window: 5,
+                factor: 2.,
+            };
+
+            #[test]
+            fn grows_when_improving_often() {
+                let actual = adapt(RULE, &[(1., 0.), (2., 0.), (2., 0.), (2., 0.), (2., 0.)]);
+
+                assert_parameters(actual, 0.1, 2.);
+            }
+
+            #[test]
+            fn shrinks_when_improving_rarely() {
+                let actual = adapt(RULE, &[(1., 0.), (1., 0.), (1., 0.), (1., 0.), (1., 0.)]);
+
+                assert_parameters(actual, 0.1, 0.5);
+            }
+
+            #[test]
+            fn waits_for_a_full_window() {
+                let actual = adapt(RULE, &[(1., 0.), (2., 0.), (3., 0.), (4., 0.)]);
+
+                assert_parameters(actual, 0.1, 1.);
+            }
+        }
+
+        mod diversity {
+            use super::*;
+
+            const RULE: AdaptationRule = AdaptationRule::Diversity {
+                threshold: 0.5,
+                factor: 2.,
+            };
+
+            #[test]
+            fn boosts_while_diversity_is_low() {
+                let actual = adapt(RULE, &[(1., 0.1), (1., 0.1)]);
+
+                assert_parameters(actual, 0.4, 4.);
+            }
+
+            #[test]
+            fn eases_back_once_diversity_recovers() {
+                let actual = adapt(RULE, &[(1., 0.1), (1., 0.1), (1., 1.)]);
+
+                assert_parameters(actual, 0.2, 2.);
+            }
+
+            #[test]
+            fn never_drops_below_base() {
+                let actual = adapt(RULE, &[(1., 0.1), (1., 1.), (1., 1.)]);
+
+                assert_parameters(actual, 0.1, 1.);
+            }
+        }
+
+        mod stagnation {
+            use super::*;
+
+            const RULE: AdaptationRule = AdaptationRule::Stagnation {
+                generations: 2,
+                factor: 2.,
+            };
+
+            #[test]
+            fn boosts_when_stagnating() {
+                let actual = adapt(RULE, &[(1., 0.), (1., 0.), (1., 0.)]);
+
+                assert_parameters(actual, 0.2, 2.);
+            }
+
+            #[test]
+            fn resets_on_improvement() {
+                let actual = adapt(RULE, &[(1., 0.), (1., 0.), (1., 0.), (2., 0.)]);
+
+                assert_parameters(actual, 0.1, 1.);
+            }
+        }
+
+        #[test]
+        fn magnitude_is_capped() {
+            let mut method = AdaptiveMutation::new(
+                GaussianMutation::new(0.1, 1.),
+                AdaptationRule::Diversity {
+                    threshold: 0.5,
+                    factor: 10.,
+                },
+            )
+            .with_max_boost(5.);
+
+            for _ in 0..3 {
+                method.adapt(&stats(1., 0.));
+            }
+
+            assert_parameters(method.parameters().unwrap(), 1., 5.);
+        }
+
+        #[test]
+        #[should_panic]
+        fn negative_magnitude() {
+            AdaptiveMutation::new(
+                GaussianMutation::new(0.5, -0.3),
+                AdaptationRule::OneFifthSuccess {
+                    window: 1,
+                    factor: 2.,
+                },
+            );
+        }
+
+        #[test]
+        fn reported_in_statistics() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+            // Never mutates, so the best fitness never improves
+            let mut ga = GeneticAlgorithm::new(
+                RouletteWheelSelection,
+                UniformCrossover,
+                AdaptiveMutation::new(
+                    GaussianMutation::new(0., 1.),
+                    AdaptationRule::Stagnation {
+                        generations: 1,
+                        factor: 2.,
+                    },
+                ),
+            );
+
+            let mut population: Vec<_> = (0..4)
+                .map(|_| TestIndividual::create(vec![1.].into_iter().collect()))
+                .collect();
+
+            let magnitudes: Vec<_> = (0..3)
+                .map(|_| {
+                    let (next, stats) = ga.evolve(&mut rng, &population);
+                    population = next;
+                    stats.mutation.unwrap().magnitude
+                })
+                .collect();
+
+            assert_relative_eq!(magnitudes.as_slice(), [1., 1., 2.].as_slice());
+        }
+    }
+}
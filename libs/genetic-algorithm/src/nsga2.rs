@@ -0,0 +1,370 @@
+use crate::*;
+
+/// An individual judged by several objectives at once,
+/// all of which are maximised
+pub trait MultiObjective: Individual {
+    fn objectives(&self) -> Vec<f32>;
+}
+
+/// NSGA-II: instead of a single fitness, individuals are ranked
+/// by which of them dominate each other (being at least as good
+/// in every objective and better in one), and spread out along
+/// every front of equally ranked ones by crowding distance.
+///
+/// Parents of the previous generation are kept, so that each
+/// generation competes with them for survival - call `evolve`
+/// on every generation in turn, just as with `GeneticAlgorithm`.
+pub struct Nsga2 {
+    crossover_method: Box<dyn CrossoverMethod>,
+    mutation_method: Box<dyn MutationMethod>,
+    /// Survivors of the previous generation
+    parents: Vec<Evaluated>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evaluated {
+    pub chromosome: Chromosome,
+    pub objectives: Vec<f32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ParetoStatistics {
+    /// Survivors no one dominates, i.e. the best trade-offs
+    /// found so far between the objectives
+    pub front: Vec<Evaluated>,
+    /// Number of fronts the survivors were picked from
+    pub fronts: usize,
+    /// Statistics of each objective in the evaluated population
+    pub objectives: Vec<ObjectiveStatistics>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ObjectiveStatistics {
+    pub min: f32,
+    pub max: f32,
+    pub avg: f32,
+}
+
+impl Nsga2 {
+    pub fn new(
+        crossover_method: impl CrossoverMethod + 'static,
+        mutation_method: impl MutationMethod + 'static,
+    ) -> Self {
+        Self {
+            crossover_method: Box::new(crossover_method),
+            mutation_method: Box::new(mutation_method),
+            parents: Vec::new(),
+        }
+    }
+
+    pub fn evolve<I>(
+        &mut self,
+        rng: &mut dyn RngCore,
+        population: &[I],
+    ) -> (Vec<I>, ParetoStatistics)
+    where
+        I: MultiObjective,
+    {
+        assert!(!population.is_empty());
+
+        let mut pool = std::mem::take(&mut self.parents);
+        pool.extend(population.iter().map(|individual| Evaluated {
+            chromosome: individual.chromosome().clone(),
+            objectives: individual.objectives(),
+        }));
+
+        let objectives: Vec<Vec<f32>> = pool.iter().map(|e| e.objectives.clone()).collect();
+        let fronts = non_dominated_sort(&objectives);
+
+        // Survivors as (index in pool, rank, crowding distance)
+        let mut survivors: Vec<(usize, usize, f32)> = Vec::with_capacity(population.len());
+
+        for (rank, front) in fronts.iter().enumerate() {
+            let missing = population.len() - survivors.len();
+
+            if missing == 0 {
+                break;
+            }
+
+            let mut crowded: Vec<(usize, usize, f32)> = front
+                .iter()
+                .zip(crowding_distance(&objectives, front))
+                .map(|(&index, distance)| (index, rank, distance))
+                .collect();
+
+            if crowded.len() > missing {
+                crowded.sort_by(|a, b| b.2.total_cmp(&a.2));
+                crowded.truncate(missing);
+            }
+
+            survivors.extend(crowded);
+        }
+
+        let children = (0..population.len())
+            .map(|_| {
+                let parent_a = &pool[Self::tournament(rng, &survivors)].chromosome;
+                let parent_b = &pool[Self::tournament(rng, &survivors)].chromosome;
+                let child = breed(
+                    &*self.crossover_method,
+                    &*self.mutation_method,
+                    rng,
+                    parent_a,
+                    parent_b,
+                );
+
+                I::create(child)
+            })
+            .collect();
+
+        let mut pool: Vec<Option<Evaluated>> = pool.into_iter().map(Some).collect();
+
+        self.parents = survivors
+            .iter()
+            .map(|&(index, _, _)| pool[index].take().unwrap())
+            .collect();
+
+        let stats = ParetoStatistics {
+            front: survivors
+                .iter()
+                .zip(&self.parents)
+                .filter(|((_, rank, _), _)| *rank == 0)
+                .map(|(_, parent)| parent.clone())
+                .collect(),
+            fronts: survivors.last().map_or(0, |(_, rank, _)| rank + 1),
+            objectives: objective_statistics(population),
+        };
+
+        (children, stats)
+    }
+
+    /// Binary tournament on (rank, crowding distance): the lower
+    /// rank wins, ties go to the less crowded survivor
+    fn tournament(rng: &mut dyn RngCore, survivors: &[(usize, usize, f32)]) -> usize {
+        let a = survivors[rng.gen_range(0..survivors.len())];
+        let b = survivors[rng.gen_range(0..survivors.len())];
+
+        let a_wins = a.1 < b.1 || (a.1 == b.1 && a.2 >= b.2);
+
+        if a_wins { a.0 } else { b.0 }
+    }
+}
+
+/// Whether `a` is at least as good as `b` in every
+/// objective and strictly better in at least one
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a >= b) && a.iter().zip(b).any(|(a, b)| a > b)
+}
+
+/// Splits individuals (given by their objectives) into fronts:
+/// the first front holds indices of everyone nobody dominates,
+/// the second of everyone dominated only by the first and so on
+pub fn non_dominated_sort(objectives: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let len = objectives.len();
+
+    let mut dominated_by = vec![0; len];
+    let mut dominating: Vec<Vec<usize>> = vec![Vec::new(); len];
+
+    for a in 0..len {
+        for b in (a + 1)..len {
+            if dominates(&objectives[a], &objectives[b]) {
+                dominating[a].push(b);
+                dominated_by[b] += 1;
+            } else if dominates(&objectives[b], &objectives[a]) {
+                dominating[b].push(a);
+                dominated_by[a] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut front: Vec<usize> = (0..len).filter(|&i| dominated_by[i] == 0).collect();
+
+    while !front.is_empty() {
+        let mut next = Vec::new();
+
+        for &a in &front {
+            for &b in &dominating[a] {
+                dominated_by[b] -= 1;
+
+                if dominated_by[b] == 0 {
+                    next.push(b);
+                }
+            }
+        }
+
+        next.sort_unstable();
+        fronts.push(front);
+        front = next;
+    }
+
+    fronts
+}
+
+/// How far each member of `front` is from its neighbours along
+/// every objective, normalised by the objective's range; the
+/// extremes of every objective get an infinite distance
+pub fn crowding_distance(objectives: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+    if front.len() <= 2 {
+        return vec![f32::INFINITY; front.len()];
+    }
+
+    let mut distances = vec![0.0; front.len()];
+    let count = objectives[front[0]].len();
+
+    let values_of = |objective: usize| -> Vec<f32> {
+        front.iter().map(|&i| objectives[i][objective]).collect()
+    };
+
+    for values in (0..count).map(values_of) {
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+        let first = order[0];
+        let last = order[order.len() - 1];
+        let range = values[last] - values[first];
+
+        distances[first] = f32::INFINITY;
+        distances[last] = f32::INFINITY;
+
+        if range <= 0. {
+            continue;
+        }
+
+        for window in order.windows(3) {
+            distances[window[1]] += (values[window[2]] - values[window[0]]) / range;
+        }
+    }
+
+    distances
+}
+
+fn objective_statistics<I>(population: &[I]) -> Vec<ObjectiveStatistics>
+where
+    I: MultiObjective,
+{
+    let objectives: Vec<Vec<f32>> = population.iter().map(|i| i.objectives()).collect();
+    let count = objectives[0].len();
+
+    (0..count)
+        .map(|objective| {
+            let values = objectives.iter().map(|o| o[objective]);
+
+            ObjectiveStatistics {
+                min: values.clone().fold(f32::INFINITY, f32::min),
+                max: values.clone().fold(f32::NEG_INFINITY, f32::max),
+                avg: values.sum::<f32>() / population.len() as f32,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestIndividual;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    /// Genes double as objectives
+    impl MultiObjective for TestIndividual {
+        fn objectives(&self) -> Vec<f32> {
+            self.chromosome().iter().cloned().collect()
+        }
+    }
+
+    fn individual(genes: &[f32]) -> TestIndividual {
+        TestIndividual::create(genes.iter().cloned().collect())
+    }
+
+    #[test]
+    fn dominance() {
+        assert!(dominates(&[2., 2.], &[1., 2.]));
+        assert!(!dominates(&[2., 2.], &[2., 2.]));
+        assert!(!dominates(&[3., 1.], &[1., 3.]));
+    }
+
+    #[test]
+    fn fronts() {
+        let objectives = vec![
+            vec![1., 1.], // dominated by 1, 2 and 3
+            vec![3., 1.],
+            vec![2., 2.],
+            vec![1., 3.],
+            vec![0., 0.], // dominated by everyone
+            vec![2., 1.], // dominated by 1 and 2
+        ];
+
+        assert_eq!(
+            non_dominated_sort(&objectives),
+            vec![vec![1, 2, 3], vec![5], vec![0], vec![4]]
+        );
+    }
+
+    #[test]
+    fn crowding() {
+        let objectives = vec![vec![0., 4.], vec![1., 3.], vec![3., 1.], vec![4., 0.]];
+
+        let distances = crowding_distance(&objectives, &[0, 1, 2, 3]);
+
+        // (3 - 0) / 4 for both objectives, likewise (4 - 1) / 4
+        assert_eq!(distances, vec![f32::INFINITY, 1.5, 1.5, f32::INFINITY]);
+    }
+
+    #[test]
+    fn small_fronts_are_never_crowded() {
+        let objectives = vec![vec![0., 4.], vec![1., 3.]];
+
+        assert_eq!(
+            crowding_distance(&objectives, &[0, 1]),
+            vec![f32::INFINITY; 2]
+        );
+    }
+
+    #[test]
+    fn evolve() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut nsga2 = Nsga2::new(UniformCrossover, GaussianMutation::new(0.5, 0.5));
+
+        let mut population = vec![
+            individual(&[0., 0.]),
+            individual(&[1., 0.]),
+            individual(&[0., 1.]),
+            individual(&[1., 1.]),
+            individual(&[2., 0.]),
+            individual(&[0., 2.]),
+        ];
+
+        let (_, first) = nsga2.evolve(&mut rng, &population);
+
+        assert_eq!(
+            first.objectives,
+            vec![
+                ObjectiveStatistics {
+                    min: 0.,
+                    max: 2.,
+                    avg: 4. / 6.,
+                };
+                2
+            ]
+        );
+
+        let front: Vec<_> = first.front.iter().map(|e| e.objectives.clone()).collect();
+        assert_eq!(front, vec![vec![1., 1.], vec![2., 0.], vec![0., 2.]]);
+
+        let mut stats = first;
+
+        for _ in 0..20 {
+            (population, stats) = nsga2.evolve(&mut rng, &population);
+        }
+
+        assert_eq!(population.len(), 6);
+        assert_eq!(stats.front.len(), 3);
+
+        // No member of the initial front is better at both objectives
+        for member in &stats.front {
+            for initial in &front {
+                assert!(!dominates(initial, &member.objectives));
+            }
+        }
+    }
+}
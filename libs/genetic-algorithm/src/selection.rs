@@ -0,0 +1,554 @@
+use crate::*;
+use rand::prelude::SliceRandom;
+
+/// Fitness proportionate selection: every individual gets a
+/// slice of the wheel as wide as its fitness and one random
+/// draw picks the slice, found by binary search over the
+/// cumulative fitness.
+///
+/// Negative (and NaN) fitness counts as zero, so such
+/// individuals are never picked. If nobody has a positive
+/// fitness, e.g. in the first generations of a simulation,
+/// every individual is equally likely to be picked.
+pub struct RouletteWheelSelection;
+
+impl RouletteWheelSelection {
+    /// Cumulative fitness of the population, the last
+    /// element being the total size of the wheel
+    fn wheel<I>(population: &[I]) -> Vec<f32>
+    where
+        I: Individual,
+    {
+        population
+            .iter()
+            .scan(0.0, |total, individual| {
+                *total += individual.fitness().max(0.0);
+                Some(*total)
+            })
+            .collect()
+    }
+
+    fn spin(rng: &mut dyn RngCore, wheel: &[f32]) -> usize {
+        let total = *wheel.last().expect("population must not be empty");
+
+        if total <= 0.0 || !total.is_finite() {
+            return rng.gen_range(0..wheel.len());
+        }
+
+        let draw = rng.gen_range(0.0..total);
+
+        // Index of the first slice ending past the draw, the
+        // min() guards against rounding at the very end
+        wheel
+            .partition_point(|&end| end <= draw)
+            .min(wheel.len() - 1)
+    }
+}
+
+impl SelectionMethod for RouletteWheelSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        let wheel = Self::wheel(population);
+
+        &population[Self::spin(rng, &wheel)]
+    }
+
+    fn select_many<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Vec<&'a I>
+    where
+        I: Individual,
+    {
+        let wheel = Self::wheel(population);
+
+        (0..count)
+            .map(|_| &population[Self::spin(rng, &wheel)])
+            .collect()
+    }
+}
+
+/// Fitness proportionate selection like `RouletteWheelSelection`,
+/// but picking a whole batch with a single spin of a wheel that
+/// has `count` evenly spaced pointers. Every individual is then
+/// picked within one of its expected number of times, instead
+/// of leaving it to chance.
+pub struct StochasticUniversalSampling;
+
+impl SelectionMethod for StochasticUniversalSampling {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        self.select_many(rng, population, 1)[0]
+    }
+
+    fn select_many<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Vec<&'a I>
+    where
+        I: Individual,
+    {
+        // E.g. when elitism keeps everyone or a species gets no offspring
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let wheel = RouletteWheelSelection::wheel(population);
+        let total = *wheel.last().expect("population must not be empty");
+
+        if total <= 0.0 || !total.is_finite() {
+            return (0..count)
+                .map(|_| &population[rng.gen_range(0..population.len())])
+                .collect();
+        }
+
+        let spacing = total / count as f32;
+        let start = rng.gen_range(0.0..spacing);
+        let mut index = 0;
+
+        let mut selected: Vec<&I> = (0..count)
+            .map(|pointer| {
+                let pointer = start + pointer as f32 * spacing;
+
+                while index < wheel.len() - 1 && wheel[index] <= pointer {
+                    index += 1;
+                }
+
+                &population[index]
+            })
+            .collect();
+
+        // Otherwise consecutive picks (e.g. pairs of parents)
+        // would mostly be the same or neighbouring individuals
+        selected.shuffle(rng);
+        selected
+    }
+}
+
+/// Selection by rank instead of raw fitness: individuals are
+/// sorted by fitness and the chance of picking each depends
+/// only on its position, so a single outlier can't take over
+/// the population and negative fitness works just fine.
+#[derive(Clone, Debug)]
+pub struct RankSelection {
+    ranking: Ranking,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ranking {
+    /// Chance grows linearly with rank, the best individual
+    /// being `pressure` times as likely to be picked as average
+    /// - 1.0 = uniformly random selection
+    /// - 2.0 = the worst individual is never picked
+    Linear { pressure: f32 },
+    /// Every individual is `base` times as likely to be picked
+    /// as the next better one
+    /// - close to 1.0 = nearly uniform selection
+    /// - close to 0.0 = nearly always the best individual
+    Exponential { base: f32 },
+}
+
+impl RankSelection {
+    pub fn new(ranking: Ranking) -> Self {
+        match ranking {
+            Ranking::Linear { pressure } if !(1. ..=2.).contains(&pressure) => {
+                panic!("Pressure is on b/w 1 & 2")
+            }
+            Ranking::Exponential { base } if !(base > 0. && base <= 1.) => {
+                panic!("Base is on b/w 0 (exclusive) & 1")
+            }
+            _ => Self { ranking },
+        }
+    }
+
+    /// Cumulative weights like `RouletteWheelSelection::wheel`,
+    /// with each individual's weight given by its rank
+    fn wheel<I>(&self, population: &[I]) -> Vec<f32>
+    where
+        I: Individual,
+    {
+        let len = population.len();
+
+        let mut ranked: Vec<usize> = (0..len).collect();
+        ranked.sort_by(|&a, &b| population[a].fitness().total_cmp(&population[b].fitness()));
+
+        // The worst individual has rank 0, the best `len - 1`
+        let mut weights = vec![0.0; len];
+
+        for (rank, index) in ranked.into_iter().enumerate() {
+            weights[index] = match self.ranking {
+                Ranking::Linear { pressure } if len > 1 => {
+                    2. - pressure + 2. * (pressure - 1.) * rank as f32 / (len - 1) as f32
+                }
+                Ranking::Linear { .. } => 1.,
+                Ranking::Exponential { base } => libm::powf(base, (len - 1 - rank) as f32),
+            };
+        }
+
+        weights
+            .into_iter()
+            .scan(0.0, |total, weight| {
+                *total += weight;
+                Some(*total)
+            })
+            .collect()
+    }
+}
+
+impl SelectionMethod for RankSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        let wheel = self.wheel(population);
+
+        &population[RouletteWheelSelection::spin(rng, &wheel)]
+    }
+
+    fn select_many<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Vec<&'a I>
+    where
+        I: Individual,
+    {
+        let wheel = self.wheel(population);
+
+        (0..count)
+            .map(|_| &population[RouletteWheelSelection::spin(rng, &wheel)])
+            .collect()
+    }
+}
+
+/// Picks `size` random contestants and lets them compete:
+/// the fittest one wins with probability `pressure`, if it
+/// doesn't the runner-up gets the same chance and so on.
+/// Only the ordering of fitness matters, so this works with
+/// negative and heavily skewed fitness values alike.
+#[derive(Clone, Debug)]
+pub struct TournamentSelection {
+    /// Number of contestants in a single tournament
+    /// - 1 = uniformly random selection
+    /// - larger = fitter individuals win more often
+    size: usize,
+
+    /// Probability of the best contestant winning
+    /// - 1.0 = the best contestant always wins
+    /// - 0.5 = a coin flip between consecutive contestants
+    pressure: f32,
+}
+
+impl TournamentSelection {
+    pub fn new(size: usize, pressure: f32) -> Self {
+        if size == 0 {
+            panic!("Tournament needs at least one contestant")
+        }
+
+        if !(0. ..=1.).contains(&pressure) {
+            panic!("Pressure is on b/w 0 & 1")
+        };
+
+        Self { size, pressure }
+    }
+}
+
+impl SelectionMethod for TournamentSelection {
+    fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> &'a I
+    where
+        I: Individual,
+    {
+        let mut contestants: Vec<&I> = (0..self.size)
+            .map(|_| {
+                population
+                    .choose(rng)
+                    .expect("population must not be empty")
+            })
+            .collect();
+
+        contestants.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+
+        let last = contestants.len() - 1;
+
+        for contestant in &contestants[..last] {
+            if rng.gen_bool(self.pressure as f64) {
+                return contestant;
+            }
+        }
+
+        contestants[last]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestIndividual;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::BTreeMap;
+    use std::iter::FromIterator;
+
+    fn histogram(
+        selection_method: impl SelectionMethod,
+        population: &[TestIndividual],
+    ) -> BTreeMap<i32, usize> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut histogram = BTreeMap::new();
+
+        for _ in 0..1000 {
+            let fitness = selection_method.select(&mut rng, population).fitness() as i32;
+
+            *histogram.entry(fitness).or_insert(0) += 1;
+        }
+
+        histogram
+    }
+
+    mod roulette_wheel_selection {
+        use super::*;
+
+        #[test]
+        fn proportional_to_fitness() {
+            let population = vec![
+                TestIndividual::new(2.),
+                TestIndividual::new(1.),
+                TestIndividual::new(4.),
+                TestIndividual::new(3.),
+            ];
+
+            let actual_histogram = histogram(RouletteWheelSelection, &population);
+            let expected_histogram = BTreeMap::from_iter([(1, 98), (2, 202), (3, 278), (4, 422)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        fn all_zero_fitness_is_uniform() {
+            let population = vec![
+                TestIndividual::new(0.),
+                TestIndividual::new(0.),
+                TestIndividual::new(0.),
+                TestIndividual::new(0.),
+            ];
+
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut actual_histogram = BTreeMap::new();
+
+            for _ in 0..1000 {
+                let individual = RouletteWheelSelection.select(&mut rng, &population);
+                let index = population
+                    .iter()
+                    .position(|i| std::ptr::eq(i, individual))
+                    .unwrap();
+
+                *actual_histogram.entry(index).or_insert(0) += 1;
+            }
+
+            let expected_histogram = BTreeMap::from_iter([(0, 243), (1, 265), (2, 261), (3, 231)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        fn negative_fitness_is_never_picked() {
+            let population = vec![
+                TestIndividual::new(-2.),
+                TestIndividual::new(1.),
+                TestIndividual::new(-4.),
+                TestIndividual::new(3.),
+            ];
+
+            let actual_histogram = histogram(RouletteWheelSelection, &population);
+            let expected_histogram = BTreeMap::from_iter([(1, 248), (3, 752)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+    }
+
+    mod tournament_selection {
+        use super::*;
+
+        fn population() -> Vec<TestIndividual> {
+            vec![
+                TestIndividual::new(2.),
+                TestIndividual::new(1.),
+                TestIndividual::new(4.),
+                TestIndividual::new(3.),
+            ]
+        }
+
+        #[test]
+        fn single_contestant_is_uniform() {
+            let actual_histogram = histogram(TournamentSelection::new(1, 1.0), &population());
+            let expected_histogram = BTreeMap::from_iter([(1, 262), (2, 246), (3, 238), (4, 254)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        fn full_pressure() {
+            let actual_histogram = histogram(TournamentSelection::new(2, 1.0), &population());
+            let expected_histogram = BTreeMap::from_iter([(1, 66), (2, 179), (3, 299), (4, 456)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        fn partial_pressure() {
+            let actual_histogram = histogram(TournamentSelection::new(3, 0.75), &population());
+            let expected_histogram = BTreeMap::from_iter([(1, 70), (2, 146), (3, 293), (4, 491)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        fn negative_fitness() {
+            let population = vec![
+                TestIndividual::new(-2.),
+                TestIndividual::new(-1.),
+                TestIndividual::new(-4.),
+                TestIndividual::new(-3.),
+            ];
+
+            let actual_histogram = histogram(TournamentSelection::new(2, 1.0), &population);
+            let expected_histogram =
+                BTreeMap::from_iter([(-4, 70), (-3, 175), (-2, 302), (-1, 453)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        #[should_panic]
+        fn empty_tournament() {
+            TournamentSelection::new(0, 1.0);
+        }
+    }
+
+    mod stochastic_universal_sampling {
+        use super::*;
+
+        fn histogram(population: &[TestIndividual]) -> BTreeMap<i32, usize> {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut histogram = BTreeMap::new();
+
+            for individual in StochasticUniversalSampling.select_many(&mut rng, population, 1000) {
+                *histogram.entry(individual.fitness() as i32).or_insert(0) += 1;
+            }
+
+            histogram
+        }
+
+        #[test]
+        fn exactly_proportional_to_fitness() {
+            let population = vec![
+                TestIndividual::new(2.),
+                TestIndividual::new(1.),
+                TestIndividual::new(4.),
+                TestIndividual::new(3.),
+            ];
+
+            let actual_histogram = histogram(&population);
+            let expected_histogram = BTreeMap::from_iter([(1, 100), (2, 200), (3, 300), (4, 400)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        fn negative_fitness_is_never_picked() {
+            let population = vec![
+                TestIndividual::new(-2.),
+                TestIndividual::new(1.),
+                TestIndividual::new(-4.),
+                TestIndividual::new(3.),
+            ];
+
+            let actual_histogram = histogram(&population);
+            let expected_histogram = BTreeMap::from_iter([(1, 250), (3, 750)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        fn picks_are_shuffled() {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let population = vec![TestIndividual::new(1.), TestIndividual::new(1.)];
+
+            let picks: Vec<_> = StochasticUniversalSampling
+                .select_many(&mut rng, &population, 10)
+                .into_iter()
+                .map(|individual| std::ptr::eq(individual, &population[0]))
+                .collect();
+
+            assert_ne!(
+                picks,
+                [
+                    true, true, true, true, true, false, false, false, false, false
+                ]
+            );
+        }
+    }
+
+    mod rank_selection {
+        use super::*;
+
+        fn population() -> Vec<TestIndividual> {
+            vec![
+                TestIndividual::new(2.),
+                TestIndividual::new(1.),
+                TestIndividual::new(400.),
+                TestIndividual::new(-3.),
+            ]
+        }
+
+        #[test]
+        fn uniform_linear_ranking() {
+            let selection = RankSelection::new(Ranking::Linear { pressure: 1. });
+
+            let actual_histogram = histogram(selection, &population());
+            let expected_histogram =
+                BTreeMap::from_iter([(-3, 229), (1, 253), (2, 248), (400, 270)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        fn linear_ranking() {
+            // Weights by rank: 0.5, 5/6, 7/6, 1.5
+            let selection = RankSelection::new(Ranking::Linear { pressure: 1.5 });
+
+            let actual_histogram = histogram(selection, &population());
+            let expected_histogram =
+                BTreeMap::from_iter([(-3, 114), (1, 207), (2, 294), (400, 385)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        fn exponential_ranking() {
+            // Weights by rank: 0.125, 0.25, 0.5, 1.0
+            let selection = RankSelection::new(Ranking::Exponential { base: 0.5 });
+
+            let actual_histogram = histogram(selection, &population());
+            let expected_histogram =
+                BTreeMap::from_iter([(-3, 56), (1, 140), (2, 264), (400, 540)]);
+
+            assert_eq!(actual_histogram, expected_histogram);
+        }
+
+        #[test]
+        #[should_panic]
+        fn rejects_too_much_pressure() {
+            RankSelection::new(Ranking::Linear { pressure: 3. });
+        }
+    }
+}
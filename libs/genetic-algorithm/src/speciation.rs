@@ -0,0 +1,273 @@
+use crate::*;
+
+/// Niching: splits the population into species of similar
+/// chromosomes, which then compete for offspring as a whole
+/// and only breed among themselves. Offspring go to species by
+/// their average fitness, i.e. fitness shared by all members,
+/// so a single strategy can't take over the whole population
+/// just by being a bit ahead early on.
+#[derive(Clone, Debug)]
+pub struct Speciation {
+    /// Max euclidean distance between chromosomes of one species
+    threshold: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Species {
+    /// Number of individuals in the species
+    pub size: usize,
+    pub avg_fitness: f32,
+    pub max_fitness: f32,
+    /// Number of children bred by the species
+    pub offspring: usize,
+}
+
+impl Speciation {
+    pub fn new(threshold: f32) -> Self {
+        if threshold < 0. {
+            panic!("Species threshold can't be negative")
+        }
+
+        Self { threshold }
+    }
+
+    /// Picks `2 * count` parents, in pairs of the same species
+    pub(crate) fn select_parents<'a, S, I>(
+        &self,
+        selection_method: &S,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> (Vec<&'a I>, Vec<Species>)
+    where
+        S: SelectionMethod,
+        I: Individual,
+    {
+        let species = self.cluster(population);
+        let offspring = Self::allocate(population, &species, count);
+
+        let mut parents = Vec::with_capacity(2 * count);
+        let mut stats = Vec::with_capacity(species.len());
+
+        for (members, offspring) in species.iter().zip(offspring) {
+            let candidates: Vec<Member<I>> = members
+                .iter()
+                .map(|&index| Member(&population[index]))
+                .collect();
+
+            parents.extend(
+                selection_method
+                    .select_many(rng, &candidates, 2 * offspring)
+                    .into_iter()
+                    .map(|member| member.0),
+            );
+
+            let fitness = members.iter().map(|&index| population[index].fitness());
+
+            stats.push(Species {
+                size: members.len(),
+                avg_fitness: fitness.clone().sum::<f32>() / members.len() as f32,
+                max_fitness: fitness.fold(f32::NEG_INFINITY, f32::max),
+                offspring,
+            });
+        }
+
+        (parents, stats)
+    }
+
+    /// Indices of the members of every species, each joining
+    /// the first species whose first member is close enough
+    fn cluster<I>(&self, population: &[I]) -> Vec<Vec<usize>>
+    where
+        I: Individual,
+    {
+        let mut species: Vec<Vec<usize>> = Vec::new();
+
+        for (index, individual) in population.iter().enumerate() {
+            let found = species.iter_mut().find(|members| {
+                distance(population[members[0]].chromosome(), individual.chromosome())
+                    <= self.threshold
+            });
+
+            match found {
+                Some(members) => members.push(index),
+                None => species.push(vec![index]),
+            }
+        }
+
+        species
+    }
+
+    /// Splits `count` children between species in proportion
+    /// to their average fitness, with largest remainders
+    /// rounded up so that all of them are handed out
+    fn allocate<I>(population: &[I], species: &[Vec<usize>], count: usize) -> Vec<usize>
+    where
+        I: Individual,
+    {
+        let mut shares: Vec<f32> = species
+            .iter()
+            .map(|members| {
+                members
+                    .iter()
+                    .map(|&index| population[index].fitness().max(0.))
+                    .sum::<f32>()
+                    / members.len() as f32
+            })
+            .collect();
+
+        let total: f32 = shares.iter().sum();
+
+        // Without any positive fitness, species breed by size
+        if total <= 0. || !total.is_finite() {
+            shares = species.iter().map(|members| members.len() as f32).collect();
+        }
+
+        let total: f32 = shares.iter().sum();
+        let exact: Vec<f32> = shares
+            .iter()
+            .map(|share| share / total * count as f32)
+            .collect();
+
+        let mut offspring: Vec<usize> = exact.iter().map(|exact| *exact as usize).collect();
+
+        let mut by_remainder: Vec<usize> = (0..species.len()).collect();
+        by_remainder.sort_by(|&a, &b| {
+            (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor()))
+        });
+
+        let missing = count - offspring.iter().sum::<usize>();
+
+        for &index in by_remainder.iter().cycle().take(missing) {
+            offspring[index] += 1;
+        }
+
+        offspring
+    }
+}
+
+fn distance(a: &Chromosome, b: &Chromosome) -> f32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// A member of a species, borrowed from the population, so
+/// that any selection method can pick among the species alone
+struct Member<'a, I>(&'a I);
+
+impl<I> Individual for Member<'_, I>
+where
+    I: Individual,
+{
+    fn create(_chromosome: Chromosome) -> Self {
+        unreachable!("members are only ever selected")
+    }
+
+    fn fitness(&self) -> f32 {
+        self.0.fitness()
+    }
+
+    fn chromosome(&self) -> &Chromosome {
+        self.0.chromosome()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestIndividual;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn individual(genes: &[f32]) -> TestIndividual {
+        TestIndividual::create(genes.iter().cloned().collect())
+    }
+
+    /// Two clusters: three small individuals around 0 and
+    /// a fitter pair around 10
+    fn population() -> Vec<TestIndividual> {
+        vec![
+            individual(&[0., 0.]),
+            individual(&[10., 10.]),
+            individual(&[0., 1.]),
+            individual(&[1., 0.]),
+            individual(&[10., 11.]),
+        ]
+    }
+
+    #[test]
+    fn cluster() {
+        let species = Speciation::new(2.).cluster(&population());
+
+        assert_eq!(species, vec![vec![0, 2, 3], vec![1, 4]]);
+    }
+
+    #[test]
+    fn zero_threshold_puts_everyone_in_their_own_species() {
+        let species = Speciation::new(0.).cluster(&population());
+
+        assert_eq!(species.len(), 5);
+    }
+
+    #[test]
+    fn allocate() {
+        // Average fitness: 2/3 and 20.5
+        let population = population();
+        let species = Speciation::new(2.).cluster(&population);
+
+        assert_eq!(Speciation::allocate(&population, &species, 10), vec![0, 10]);
+        assert_eq!(
+            Speciation::allocate(&population, &species, 100),
+            vec![3, 97]
+        );
+    }
+
+    #[test]
+    fn allocate_without_fitness() {
+        let population = vec![
+            individual(&[0., 0.]),
+            individual(&[-10., 10.]),
+            individual(&[0., 0.]),
+        ];
+        let species = Speciation::new(1.).cluster(&population);
+
+        assert_eq!(Speciation::allocate(&population, &species, 3), vec![2, 1]);
+    }
+
+    #[test]
+    fn parents_are_paired_within_species() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = population();
+
+        let (parents, species) =
+            Speciation::new(2.).select_parents(&RouletteWheelSelection, &mut rng, &population, 100);
+
+        assert_eq!(parents.len(), 200);
+
+        for pair in parents.chunks_exact(2) {
+            let distance = distance(pair[0].chromosome(), pair[1].chromosome());
+            assert!(distance <= 2.);
+        }
+
+        assert_eq!(
+            species,
+            vec![
+                Species {
+                    size: 3,
+                    avg_fitness: 2. / 3.,
+                    max_fitness: 1.,
+                    offspring: 3,
+                },
+                Species {
+                    size: 2,
+                    avg_fitness: 20.5,
+                    max_fitness: 21.,
+                    offspring: 97,
+                },
+            ]
+        );
+    }
+}
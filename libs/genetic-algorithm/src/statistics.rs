@@ -0,0 +1,207 @@
+use crate::*;
+
+#[derive(Clone, Debug)]
+pub struct Statistics {
+    pub min_fitness: f32,
+    pub max_fitness: f32,
+    pub avg_fitness: f32,
+    pub median_fitness: f32,
+    /// Population (not sample) standard deviation of fitness
+    pub std_dev_fitness: f32,
+    /// Fitness at each percentile requested through
+    /// `GeneticAlgorithm::with_percentiles`, in that order
+    pub percentiles: Vec<Percentile>,
+    /// Index of the fittest individual in the evaluated population
+    pub best_index: usize,
+    /// Mean euclidean distance of chromosomes from their centroid,
+    /// 0.0 once the whole population shares a single genotype
+    pub diversity: f32,
+    /// Fitness of the individuals carried over unchanged
+    /// by elitism, best first (empty without elitism)
+    pub elite_fitness: Vec<f32>,
+    /// Mutation parameters used to breed the next
+    /// generation, if the mutation method has them
+    pub mutation: Option<MutationParameters>,
+    /// Species the population was split into, in order of
+    /// appearance (empty without speciation)
+    pub species: Vec<Species>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Percentile {
+    /// In range 0..=100
+    pub percentile: f32,
+    pub fitness: f32,
+}
+
+impl Statistics {
+    /// Percentiles (in range 0..=100) are reported besides the median
+    pub fn new<I>(population: &[I], percentiles: &[f32]) -> Self
+    where
+        I: Individual,
+    {
+        Self::from_individuals(
+            population.iter().map(|i| (i.fitness(), i.chromosome())),
+            percentiles,
+        )
+    }
+
+    /// Statistics of individuals given by their fitness and
+    /// chromosome, e.g. gathered from several populations
+    pub(crate) fn from_individuals<'a>(
+        individuals: impl Iterator<Item = (f32, &'a Chromosome)>,
+        percentiles: &[f32],
+    ) -> Self {
+        let (fitness, chromosomes): (Vec<f32>, Vec<&Chromosome>) = individuals.unzip();
+
+        assert!(!fitness.is_empty());
+
+        let mut sorted = fitness.clone();
+        sorted.sort_by(f32::total_cmp);
+
+        let len = sorted.len() as f32;
+        let avg_fitness = sorted.iter().sum::<f32>() / len;
+        let variance = sorted
+            .iter()
+            .map(|fitness| (fitness - avg_fitness).powi(2))
+            .sum::<f32>()
+            / len;
+
+        let best_index = fitness
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| index)
+            .unwrap();
+
+        Self {
+            min_fitness: sorted[0],
+            max_fitness: sorted[sorted.len() - 1],
+            avg_fitness,
+            median_fitness: percentile(&sorted, 50.),
+            std_dev_fitness: variance.sqrt(),
+            percentiles: percentiles
+                .iter()
+                .map(|&p| Percentile {
+                    percentile: p,
+                    fitness: percentile(&sorted, p),
+                })
+                .collect(),
+            best_index,
+            diversity: diversity(&chromosomes),
+            elite_fitness: Vec::new(),
+            mutation: None,
+            species: Vec::new(),
+        }
+    }
+}
+
+/// Linearly interpolated between the two closest ranks
+fn percentile(sorted: &[f32], percentile: f32) -> f32 {
+    let rank = percentile / 100. * (sorted.len() - 1) as f32;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f32)
+}
+
+fn diversity(chromosomes: &[&Chromosome]) -> f32 {
+    let genes = chromosomes[0].len();
+    let mut centroid = vec![0.0; genes];
+
+    for chromosome in chromosomes {
+        for (sum, gene) in centroid.iter_mut().zip(chromosome.iter()) {
+            *sum += gene;
+        }
+    }
+
+    for sum in &mut centroid {
+        *sum /= chromosomes.len() as f32;
+    }
+
+    let total_distance: f32 = chromosomes
+        .iter()
+        .map(|chromosome| {
+            chromosome
+                .iter()
+                .zip(&centroid)
+                .map(|(gene, center)| (gene - center).powi(2))
+                .sum::<f32>()
+                .sqrt()
+        })
+        .sum();
+
+    total_distance / chromosomes.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestIndividual;
+    use approx::assert_relative_eq;
+
+    fn individual(genes: &[f32]) -> TestIndividual {
+        TestIndividual::create(genes.iter().cloned().collect())
+    }
+
+    #[test]
+    fn fitness_distribution() {
+        // Fitness is the sum of genes: 3, 1, 7, 5
+        let population = vec![
+            individual(&[1., 2.]),
+            individual(&[0., 1.]),
+            individual(&[3., 4.]),
+            individual(&[2., 3.]),
+        ];
+
+        let stats = Statistics::new(&population, &[0., 25., 90., 100.]);
+
+        assert_relative_eq!(stats.min_fitness, 1.);
+        assert_relative_eq!(stats.max_fitness, 7.);
+        assert_relative_eq!(stats.avg_fitness, 4.);
+        assert_relative_eq!(stats.median_fitness, 4.);
+        assert_relative_eq!(stats.std_dev_fitness, 5.0f32.sqrt());
+        assert_eq!(stats.best_index, 2);
+
+        let percentiles: Vec<_> = stats.percentiles.iter().map(|p| p.percentile).collect();
+        let fitness: Vec<_> = stats.percentiles.iter().map(|p| p.fitness).collect();
+
+        assert_eq!(percentiles, vec![0., 25., 90., 100.]);
+        assert_relative_eq!(fitness.as_slice(), [1., 2.5, 6.4, 7.].as_slice());
+    }
+
+    #[test]
+    fn median_of_odd_population() {
+        let population = vec![individual(&[5.]), individual(&[1.]), individual(&[3.])];
+
+        let stats = Statistics::new(&population, &[]);
+
+        assert_relative_eq!(stats.median_fitness, 3.);
+        assert!(stats.percentiles.is_empty());
+    }
+
+    #[test]
+    fn diversity() {
+        // Corners of a square around (1, 1), each sqrt(2) away
+        let population = vec![
+            individual(&[0., 0.]),
+            individual(&[2., 0.]),
+            individual(&[0., 2.]),
+            individual(&[2., 2.]),
+        ];
+
+        let stats = Statistics::new(&population, &[]);
+
+        assert_relative_eq!(stats.diversity, 2.0f32.sqrt());
+    }
+
+    #[test]
+    fn no_diversity_in_clones() {
+        let population = vec![individual(&[1., 2., 3.]); 4];
+
+        let stats = Statistics::new(&population, &[]);
+
+        assert_relative_eq!(stats.diversity, 0.);
+        assert_relative_eq!(stats.std_dev_fitness, 0.);
+    }
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+/// The function a neuron applies to its weighted sum
+/// (plus bias) before passing it on to the next layer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+    /// max(0, x) - the original behaviour of every neuron
+    #[default]
+    #[serde(rename = "relu")]
+    ReLU,
+    /// Like ReLU but negative inputs are scaled by the
+    /// given slope instead of being cut off at zero
+    #[serde(rename = "leaky_relu")]
+    LeakyReLU(f32),
+    /// Squashes into (0, 1)
+    Sigmoid,
+    /// Squashes into (-1, 1)
+    Tanh,
+    /// Passes the value through untouched
+    Identity,
+    /// x / (1 + |x|), a cheaper tanh lookalike in (-1, 1)
+    Softsign,
+    /// 1 for non-negative inputs, 0 otherwise
+    Step,
+}
+
+impl Activation {
+    /// Transcendental functions go through libm rather than
+    /// std, so outputs are bit-identical on every target
+    pub fn apply(&self, x: f32) -> f32 {
+        match self {
+            Self::ReLU => x.max(0.0),
+            Self::LeakyReLU(slope) => {
+                if x >= 0.0 {
+                    x
+                } else {
+                    slope * x
+                }
+            }
+            Self::Sigmoid => 1.0 / (1.0 + libm::expf(-x)),
+            Self::Tanh => libm::tanhf(x),
+            Self::Identity => x,
+            Self::Softsign => x / (1.0 + x.abs()),
+            Self::Step => {
+                if x >= 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(Activation::ReLU, -2.0, 0.0)]
+    #[test_case(Activation::ReLU, 2.0, 2.0)]
+    #[test_case(Activation::LeakyReLU(0.1), -2.0, -0.2)]
+    #[test_case(Activation::LeakyReLU(0.1), 2.0, 2.0)]
+    #[test_case(Activation::Sigmoid, 0.0, 0.5)]
+    #[test_case(Activation::Sigmoid, 2.0, 0.880797)]
+    #[test_case(Activation::Tanh, -1.0, -0.7615942)]
+    #[test_case(Activation::Tanh, 0.0, 0.0)]
+    #[test_case(Activation::Identity, -3.5, -3.5)]
+    #[test_case(Activation::Softsign, -1.0, -0.5)]
+    #[test_case(Activation::Softsign, 3.0, 0.75)]
+    #[test_case(Activation::Step, -0.1, 0.0)]
+    #[test_case(Activation::Step, 0.0, 1.0)]
+    fn apply(activation: Activation, x: f32, expected: f32) {
+        assert_almost_eq!(activation.apply(x), expected);
+    }
+}
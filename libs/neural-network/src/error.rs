@@ -0,0 +1,68 @@
+use std::{error, fmt, io};
+
+#[derive(Debug)]
+pub enum NetworkError {
+    /// A network needs at least an input and an output layer
+    NotEnoughLayers(usize),
+    /// Every layer needs at least one neuron, this one (by index) has none
+    EmptyLayer(usize),
+    /// The number of weights doesn't match what the topology needs
+    WeightsMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    /// The data was written by a format version we can't read
+    UnsupportedVersion(u32),
+    /// The data isn't a saved network or is truncated
+    Malformed(String),
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEnoughLayers(layers) => {
+                write!(f, "network needs at least 2 layers, got {layers}")
+            }
+            Self::EmptyLayer(layer) => write!(f, "layer {layer} has no neurons"),
+            Self::WeightsMismatch { expected, actual } if actual > expected => {
+                write!(
+                    f,
+                    "more weights than necessary: expected {expected}, got {actual}"
+                )
+            }
+            Self::WeightsMismatch { expected, actual } => {
+                write!(f, "not enough weights: expected {expected}, got {actual}")
+            }
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported network format version {version}")
+            }
+            Self::Malformed(reason) => write!(f, "malformed network data: {reason}"),
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Json(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl error::Error for NetworkError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NetworkError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for NetworkError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
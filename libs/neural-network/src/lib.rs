@@ -2,46 +2,116 @@
 /// that simulates how an agent (bird) identifies and runs
 /// towards a target (bird food) to get a positive feedback (eat it).
 use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
 use std::iter::*;
 
 #[macro_use]
 mod utils;
+mod activation;
+mod error;
+mod storage;
 
-#[derive(Debug)]
+pub use self::{activation::*, error::*, storage::*};
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Network {
     layers: Vec<Layer>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LayerTopology {
     pub neurons: usize,
+    /// Applied by every neuron of this layer, ignored
+    /// for the input layer since it has no neurons of its own
+    pub activation: Activation,
 }
 
 impl Network {
     pub fn random(rng: &mut dyn RngCore, layers: &[LayerTopology]) -> Self {
         let layers = layers
             .windows(2)
-            .map(|layers| Layer::random(rng, layers[0].neurons, layers[1].neurons))
+            .map(|layers| {
+                Layer::random(
+                    rng,
+                    layers[0].neurons,
+                    layers[1].neurons,
+                    layers[1].activation,
+                )
+            })
             .collect();
 
         Self { layers }
     }
 
     pub fn from_weights(layers: &[LayerTopology], weights: impl IntoIterator<Item = f32>) -> Self {
-        assert!(layers.len() > 1);
+        Self::try_from_weights(layers, weights).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_from_weights(
+        layers: &[LayerTopology],
+        weights: impl IntoIterator<Item = f32>,
+    ) -> Result<Self, NetworkError> {
+        if layers.len() < 2 {
+            return Err(NetworkError::NotEnoughLayers(layers.len()));
+        }
+
+        if let Some(layer) = layers.iter().position(|layer| layer.neurons == 0) {
+            return Err(NetworkError::EmptyLayer(layer));
+        }
+
+        // Every neuron has a bias and one weight per input; sizes
+        // come from saved files too, so they may be absurdly large
+        let expected = layers
+            .windows(2)
+            .try_fold(0usize, |sum, layers| {
+                layers[0]
+                    .neurons
+                    .checked_add(1)?
+                    .checked_mul(layers[1].neurons)?
+                    .checked_add(sum)
+            })
+            .ok_or_else(|| NetworkError::Malformed("too many weights to count".into()))?;
+
+        let weights: Vec<f32> = weights.into_iter().collect();
+
+        if weights.len() != expected {
+            return Err(NetworkError::WeightsMismatch {
+                expected,
+                actual: weights.len(),
+            });
+        }
 
         let mut weights = weights.into_iter();
 
         let layers = layers
             .windows(2)
-            .map(|layers| Layer::from_weights(layers[0].neurons, layers[1].neurons, &mut weights))
+            .map(|layers| {
+                Layer::from_weights(
+                    layers[0].neurons,
+                    layers[1].neurons,
+                    layers[1].activation,
+                    &mut weights,
+                )
+            })
             .collect();
 
-        if weights.next().is_some() {
-            panic!("More weights than necessary")
-        }
+        Ok(Self { layers })
+    }
 
-        Self { layers }
+    /// The topology this network was built from, with
+    /// the input layer reported as `Activation::Identity`
+    pub fn topology(&self) -> Vec<LayerTopology> {
+        let input = LayerTopology {
+            neurons: self.layers[0].neurons[0].weights.len(),
+            activation: Activation::Identity,
+        };
+
+        once(input)
+            .chain(self.layers.iter().map(|layer| LayerTopology {
+                neurons: layer.neurons.len(),
+                activation: layer.activation,
+            }))
+            .collect()
     }
 
     pub fn propogate(&self, inputs: Vec<f32>) -> Vec<f32> {
@@ -59,41 +129,54 @@ impl Network {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 struct Layer {
     neurons: Vec<Neuron>,
+    activation: Activation,
 }
 
 impl Layer {
-    fn random(rng: &mut dyn RngCore, input_size: usize, output_size: usize) -> Self {
+    fn random(
+        rng: &mut dyn RngCore,
+        input_size: usize,
+        output_size: usize,
+        activation: Activation,
+    ) -> Self {
         let neurons = (0..output_size)
             .map(|_| Neuron::random(rng, input_size))
             .collect();
 
-        Self { neurons }
+        Self {
+            neurons,
+            activation,
+        }
     }
 
     fn from_weights(
         input_size: usize,
         output_size: usize,
+        activation: Activation,
         weights: &mut dyn Iterator<Item = f32>,
     ) -> Self {
         let neurons = (0..output_size)
             .map(|_| Neuron::from_weights(input_size, weights))
             .collect();
 
-        Self { neurons }
+        Self {
+            neurons,
+            activation,
+        }
     }
 
     fn propogate(&self, inputs: Vec<f32>) -> Vec<f32> {
         self.neurons
             .iter()
-            .map(|neuron| neuron.propogate(&inputs))
+            .map(|neuron| neuron.propogate(&inputs, self.activation))
             .collect()
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 struct Neuron {
     bias: f32,
     weights: Vec<f32>,
@@ -118,7 +201,7 @@ impl Neuron {
         Self { bias, weights }
     }
 
-    fn propogate(&self, inputs: &[f32]) -> f32 {
+    fn propogate(&self, inputs: &[f32], activation: Activation) -> f32 {
         assert_eq!(inputs.len(), self.weights.len());
 
         let output = self
@@ -128,7 +211,7 @@ impl Neuron {
             .map(|(weight, input)| input * weight)
             .sum::<f32>();
 
-        (output + self.bias).max(0.0)
+        activation.apply(output + self.bias)
     }
 }
 
@@ -157,12 +240,46 @@ mod tests {
             weights: vec![-0.3, 0.8],
         };
 
-        assert_almost_eq!(neuron.propogate(&[-10., -10.]), 0.0);
+        assert_almost_eq!(neuron.propogate(&[-10., -10.], Activation::ReLU), 0.0);
 
         assert_almost_eq!(
-            neuron.propogate(&[0.5, 1.0]),
+            neuron.propogate(&[0.5, 1.0], Activation::ReLU),
             (-0.3 * 0.5) + (0.8 * 1.0) + 0.5
         );
+
+        // Negative outputs survive anything but ReLU and step
+        assert_almost_eq!(
+            neuron.propogate(&[-10., -10.], Activation::Identity),
+            (-0.3 * -10.) + (0.8 * -10.) + 0.5
+        );
+        assert_almost_eq!(
+            neuron.propogate(&[-10., -10.], Activation::Tanh),
+            (-4.5f32).tanh()
+        );
+    }
+
+    #[test]
+    fn propogate_honours_layer_activations() {
+        let layers = &[
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Identity,
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Identity,
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Tanh,
+            },
+        ];
+
+        // Hidden: 0.5 + 1.0 * 1.0 - 1.0 * 3.0 = -1.5
+        // Output: tanh(0.0 + 2.0 * -1.5) = tanh(-3.0)
+        let network = Network::from_weights(layers, vec![0.5, 1.0, -1.0, 0.0, 2.0]);
+
+        assert_almost_eq!(network.propogate(vec![1.0, 3.0])[0], (-3.0f32).tanh());
     }
 
     #[test]
@@ -174,18 +291,20 @@ mod tests {
                         bias: 0.1,
                         weights: vec![0.2, 0.3, 0.4],
                     }],
+                    activation: Activation::ReLU,
                 },
                 Layer {
                     neurons: vec![Neuron {
                         bias: 0.5,
                         weights: vec![0.6, 0.7, 0.8],
                     }],
+                    activation: Activation::Tanh,
                 },
             ],
         };
 
         let actual: Vec<f32> = network.weights().collect();
-        let expected = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
+        let expected = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
 
         for i in 0..expected.len() {
             assert_almost_eq!(actual[i], expected[i])
@@ -194,7 +313,16 @@ mod tests {
 
     #[test]
     fn from_weights() {
-        let layers = &[LayerTopology { neurons: 3 }, LayerTopology { neurons: 2 }];
+        let layers = &[
+            LayerTopology {
+                neurons: 3,
+                activation: Activation::ReLU,
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Sigmoid,
+            },
+        ];
 
         let weights = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
         let network = Network::from_weights(layers, weights.clone());
@@ -203,5 +331,78 @@ mod tests {
         for i in 0..actual.len() {
             assert_almost_eq!(weights[i], actual[i]);
         }
+
+        assert_eq!(network.layers[0].activation, Activation::Sigmoid);
+    }
+
+    #[test]
+    fn try_from_weights() {
+        let layers = &[
+            LayerTopology {
+                neurons: 3,
+                activation: Activation::ReLU,
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::ReLU,
+            },
+        ];
+
+        assert!(matches!(
+            Network::try_from_weights(layers, vec![0.1; 7]),
+            Err(NetworkError::WeightsMismatch {
+                expected: 8,
+                actual: 7
+            })
+        ));
+        assert!(matches!(
+            Network::try_from_weights(layers, vec![0.1; 9]),
+            Err(NetworkError::WeightsMismatch {
+                expected: 8,
+                actual: 9
+            })
+        ));
+        assert!(matches!(
+            Network::try_from_weights(&layers[..1], vec![]),
+            Err(NetworkError::NotEnoughLayers(1))
+        ));
+
+        let empty = [
+            layers[0],
+            LayerTopology {
+                neurons: 0,
+                ..layers[1]
+            },
+            layers[1],
+        ];
+
+        assert!(matches!(
+            Network::try_from_weights(&empty, vec![0.1; 2]),
+            Err(NetworkError::EmptyLayer(1))
+        ));
+        assert!(Network::try_from_weights(layers, vec![0.1; 8]).is_ok());
+    }
+
+    #[test]
+    fn topology() {
+        let layers = [
+            LayerTopology {
+                neurons: 3,
+                activation: Activation::Identity,
+            },
+            LayerTopology {
+                neurons: 5,
+                activation: Activation::ReLU,
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Softsign,
+            },
+        ];
+
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network = Network::random(&mut rng, &layers);
+
+        assert_eq!(network.topology(), layers);
     }
 }
@@ -0,0 +1,329 @@
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// Bumped whenever the layout of a saved network changes,
+/// so older files are rejected instead of misread
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Leading bytes of the binary format
+const MAGIC: &[u8; 4] = b"BDNN";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// Human readable, e.g. for inspecting a trained brain
+    Json,
+    /// Compact little-endian encoding
+    Binary,
+}
+
+/// What actually gets written to disk, in either format
+#[derive(Serialize, Deserialize)]
+struct SavedNetwork {
+    version: u32,
+    layers: Vec<LayerTopology>,
+    weights: Vec<f32>,
+}
+
+impl Network {
+    pub fn save(&self, writer: impl Write, format: Format) -> Result<(), NetworkError> {
+        let saved = SavedNetwork {
+            version: FORMAT_VERSION,
+            layers: self.topology(),
+            weights: self.weights().collect(),
+        };
+
+        match format {
+            Format::Json => serde_json::to_writer_pretty(writer, &saved)?,
+            Format::Binary => binary::write(writer, &saved)?,
+        }
+
+        Ok(())
+    }
+
+    pub fn load(reader: impl Read, format: Format) -> Result<Self, NetworkError> {
+        let saved: SavedNetwork = match format {
+            Format::Json => serde_json::from_reader(reader)?,
+            Format::Binary => binary::read(reader)?,
+        };
+
+        if saved.version != FORMAT_VERSION {
+            return Err(NetworkError::UnsupportedVersion(saved.version));
+        }
+
+        Self::try_from_weights(&saved.layers, saved.weights)
+    }
+}
+
+/// Layout, all numbers little-endian:
+///
+/// ```text
+/// magic    b"BDNN"
+/// version  u32
+/// layers   u32, then per layer: neurons u32, activation u8, slope f32
+/// weights  u32, then per weight: f32
+/// ```
+mod binary {
+    use super::*;
+
+    pub(super) fn write(mut writer: impl Write, saved: &SavedNetwork) -> Result<(), NetworkError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&saved.version.to_le_bytes())?;
+
+        write_len(&mut writer, saved.layers.len())?;
+
+        for layer in &saved.layers {
+            let (tag, slope) = match layer.activation {
+                Activation::ReLU => (0, 0.0),
+                Activation::LeakyReLU(slope) => (1, slope),
+                Activation::Sigmoid => (2, 0.0),
+                Activation::Tanh => (3, 0.0),
+                Activation::Identity => (4, 0.0),
+                Activation::Softsign => (5, 0.0),
+                Activation::Step => (6, 0.0),
+            };
+
+            write_len(&mut writer, layer.neurons)?;
+            writer.write_all(&[tag])?;
+            writer.write_all(&f32::to_le_bytes(slope))?;
+        }
+
+        write_len(&mut writer, saved.weights.len())?;
+
+        for weight in &saved.weights {
+            writer.write_all(&weight.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn read(mut reader: impl Read) -> Result<SavedNetwork, NetworkError> {
+        let mut magic = [0; 4];
+        read_exact(&mut reader, &mut magic)?;
+
+        if &magic != MAGIC {
+            return Err(NetworkError::Malformed("not a saved network".into()));
+        }
+
+        let version = read_u32(&mut reader)?;
+
+        if version != FORMAT_VERSION {
+            return Err(NetworkError::UnsupportedVersion(version));
+        }
+
+        let layers = (0..read_u32(&mut reader)?)
+            .map(|_| {
+                let neurons = read_u32(&mut reader)? as usize;
+
+                let mut tag = [0];
+                read_exact(&mut reader, &mut tag)?;
+
+                let slope = read_f32(&mut reader)?;
+
+                let activation = match tag[0] {
+                    0 => Activation::ReLU,
+                    1 => Activation::LeakyReLU(slope),
+                    2 => Activation::Sigmoid,
+                    3 => Activation::Tanh,
+                    4 => Activation::Identity,
+                    5 => Activation::Softsign,
+                    6 => Activation::Step,
+                    tag => {
+                        return Err(NetworkError::Malformed(format!("unknown activation {tag}")));
+                    }
+                };
+
+                Ok(LayerTopology {
+                    neurons,
+                    activation,
+                })
+            })
+            .collect::<Result<_, NetworkError>>()?;
+
+        let weights = (0..read_u32(&mut reader)?)
+            .map(|_| read_f32(&mut reader))
+            .collect::<Result<_, _>>()?;
+
+        Ok(SavedNetwork {
+            version,
+            layers,
+            weights,
+        })
+    }
+
+    fn write_len(writer: &mut impl Write, len: usize) -> Result<(), NetworkError> {
+        let len = u32::try_from(len)
+            .map_err(|_| NetworkError::Malformed(format!("{len} doesn't fit in a u32")))?;
+
+        writer.write_all(&len.to_le_bytes())?;
+
+        Ok(())
+    }
+
+    fn read_u32(reader: &mut impl Read) -> Result<u32, NetworkError> {
+        let mut bytes = [0; 4];
+        read_exact(reader, &mut bytes)?;
+
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_f32(reader: &mut impl Read) -> Result<f32, NetworkError> {
+        let mut bytes = [0; 4];
+        read_exact(reader, &mut bytes)?;
+
+        Ok(f32::from_le_bytes(bytes))
+    }
+
+    /// Like `Read::read_exact`, but running out of data
+    /// means the file is truncated rather than an IO failure
+    fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<(), NetworkError> {
+        reader.read_exact(buf).map_err(|err| match err.kind() {
+            std::io::ErrorKind::UnexpectedEof => NetworkError::Malformed("truncated data".into()),
+            _ => NetworkError::Io(err),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn network() -> Network {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        Network::random(
+            &mut rng,
+            &[
+                LayerTopology {
+                    neurons: 3,
+                    activation: Activation::Identity,
+                },
+                LayerTopology {
+                    neurons: 4,
+                    activation: Activation::LeakyReLU(0.01),
+                },
+                LayerTopology {
+                    neurons: 2,
+                    activation: Activation::Tanh,
+                },
+            ],
+        )
+    }
+
+    fn roundtrip(format: Format) {
+        let network = network();
+
+        let mut bytes = Vec::new();
+        network.save(&mut bytes, format).unwrap();
+
+        let loaded = Network::load(bytes.as_slice(), format).unwrap();
+
+        assert_eq!(loaded, network);
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        roundtrip(Format::Json);
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        roundtrip(Format::Binary);
+    }
+
+    #[test]
+    fn json_is_readable() {
+        let json = r#"{
+            "version": 1,
+            "layers": [
+                { "neurons": 2, "activation": "identity" },
+                { "neurons": 1, "activation": { "leaky_relu": 0.1 } }
+            ],
+            "weights": [0.5, -1.0, 1.0]
+        }"#;
+
+        let network = Network::load(json.as_bytes(), Format::Json).unwrap();
+
+        assert_almost_eq!(network.propogate(vec![2.0, 1.0])[0], -0.05);
+    }
+
+    #[test]
+    fn binary_is_compact() {
+        let mut bytes = Vec::new();
+        network().save(&mut bytes, Format::Binary).unwrap();
+
+        // Header, 3 layers and 4 * (3 + 1) + 2 * (4 + 1) weights
+        assert_eq!(bytes.len(), 4 + 4 + 4 + 3 * 9 + 4 + 26 * 4);
+    }
+
+    #[test]
+    fn mismatched_weights() {
+        let json = r#"{
+            "version": 1,
+            "layers": [
+                { "neurons": 2, "activation": "identity" },
+                { "neurons": 1, "activation": "relu" }
+            ],
+            "weights": [0.5, -1.0, 1.0, 2.0]
+        }"#;
+
+        let err = Network::load(json.as_bytes(), Format::Json).unwrap_err();
+
+        assert!(matches!(
+            err,
+            NetworkError::WeightsMismatch {
+                expected: 3,
+                actual: 4
+            }
+        ));
+    }
+
+    #[test]
+    fn oversized_layers() {
+        let json = format!(
+            r#"{{
+                "version": 1,
+                "layers": [
+                    {{ "neurons": {}, "activation": "identity" }},
+                    {{ "neurons": {}, "activation": "relu" }}
+                ],
+                "weights": [0.5]
+            }}"#,
+            u32::MAX,
+            usize::MAX,
+        );
+
+        let err = Network::load(json.as_bytes(), Format::Json).unwrap_err();
+
+        assert!(matches!(err, NetworkError::Malformed(_)));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let json = r#"{ "version": 99, "layers": [], "weights": [] }"#;
+
+        let err = Network::load(json.as_bytes(), Format::Json).unwrap_err();
+
+        assert!(matches!(err, NetworkError::UnsupportedVersion(99)));
+    }
+
+    #[test]
+    fn truncated_binary() {
+        let mut bytes = Vec::new();
+        network().save(&mut bytes, Format::Binary).unwrap();
+        bytes.truncate(bytes.len() - 1);
+
+        let err = Network::load(bytes.as_slice(), Format::Binary).unwrap_err();
+
+        assert!(matches!(err, NetworkError::Malformed(_)));
+    }
+
+    #[test]
+    fn not_a_network() {
+        let err = Network::load(&b"PNG\0 what is this"[..], Format::Binary).unwrap_err();
+
+        assert!(matches!(err, NetworkError::Malformed(_)));
+    }
+}
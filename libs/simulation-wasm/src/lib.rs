@@ -1,21 +1,43 @@
+use lib_genetic_algorithm as ga;
 use lib_simulation as sim;
 use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
 pub struct Simulation {
-    rng: ThreadRng,
+    seed: u64,
+    rng: ChaCha8Rng,
     sim: sim::Simulation,
 }
 
 #[wasm_bindgen]
 impl Simulation {
+    /// Starts from a random seed, which can be read back
+    /// through `seed()` to replay the same run later
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
-        let mut rng = thread_rng();
-        let sim = sim::Simulation::random(&mut rng);
+        Self::with_seed(thread_rng().r#gen())
+    }
+
+    /// ChaCha is portable, so the same seed gives the same
+    /// run in the browser and in native lib-simulation
+    #[wasm_bindgen(js_name = withSeed)]
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_config(seed, sim::Config::default())
+    }
+
+    /// Like `withSeed`, with settings from a JSON config
+    /// (see lib-simulation's `Config`), e.g. `{"world_predators": 5}`
+    #[wasm_bindgen(js_name = withConfig)]
+    pub fn with_config(seed: u64, config: &str) -> Result<Simulation, JsError> {
+        let config = sim::Config::from_json(config)?;
 
-        Self { rng, sim }
+        Ok(Self::from_config(seed, config))
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
     }
 
     pub fn world(&self) -> World {
@@ -26,13 +48,17 @@ impl Simulation {
         self.sim.step(&mut self.rng);
     }
 
-    pub fn train(&mut self) -> String {
-        let stats = self.sim.train(&mut self.rng);
+    pub fn train(&mut self) -> Statistics {
+        Statistics::from(&self.sim.train(&mut self.rng))
+    }
+}
+
+impl Simulation {
+    fn from_config(seed: u64, config: sim::Config) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let sim = sim::Simulation::random(config, &mut rng);
 
-        format!(
-            "min={:.2}, max={:.2}, avg={:.2}",
-            stats.min_fitness, stats.max_fitness, stats.avg_fitness,
-        )
+        Self { seed, rng, sim }
     }
 }
 
@@ -42,12 +68,77 @@ impl Default for Simulation {
     }
 }
 
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Statistics {
+    pub min_fitness: f32,
+    pub max_fitness: f32,
+    pub avg_fitness: f32,
+    pub median_fitness: f32,
+    pub std_dev_fitness: f32,
+    pub best_index: usize,
+    pub diversity: f32,
+    /// Unless islands have adapted them differently
+    pub mut_chance: Option<f32>,
+    pub mut_coeff: Option<f32>,
+    pub predator_avg_fitness: Option<f32>,
+    pub predator_max_fitness: Option<f32>,
+
+    #[wasm_bindgen(getter_with_clone)]
+    pub percentiles: Vec<Percentile>,
+
+    #[wasm_bindgen(getter_with_clone)]
+    pub elite_fitness: Vec<f32>,
+}
+
+impl From<&sim::Statistics> for Statistics {
+    fn from(stats: &sim::Statistics) -> Self {
+        let predators = stats.predators.as_ref();
+        let stats = &stats.birds;
+
+        Self {
+            min_fitness: stats.min_fitness,
+            max_fitness: stats.max_fitness,
+            avg_fitness: stats.avg_fitness,
+            median_fitness: stats.median_fitness,
+            std_dev_fitness: stats.std_dev_fitness,
+            best_index: stats.best_index,
+            diversity: stats.diversity,
+            mut_chance: stats.mutation.map(|m| m.probability),
+            mut_coeff: stats.mutation.map(|m| m.magnitude),
+            predator_avg_fitness: predators.map(|p| p.avg_fitness),
+            predator_max_fitness: predators.map(|p| p.max_fitness),
+            percentiles: stats.percentiles.iter().map(Percentile::from).collect(),
+            elite_fitness: stats.elite_fitness.clone(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Percentile {
+    pub percentile: f32,
+    pub fitness: f32,
+}
+
+impl From<&ga::Percentile> for Percentile {
+    fn from(percentile: &ga::Percentile) -> Self {
+        Self {
+            percentile: percentile.percentile,
+            fitness: percentile.fitness,
+        }
+    }
+}
+
 #[wasm_bindgen]
 #[derive(Clone, Debug)]
 pub struct World {
     #[wasm_bindgen(getter_with_clone)]
     pub animals: Vec<Animal>,
 
+    #[wasm_bindgen(getter_with_clone)]
+    pub predators: Vec<Animal>,
+
     #[wasm_bindgen(getter_with_clone)]
     pub foods: Vec<Food>,
 }
@@ -55,9 +146,14 @@ pub struct World {
 impl From<&sim::World> for World {
     fn from(world: &sim::World) -> Self {
         let animals = world.animals().iter().map(Animal::from).collect();
+        let predators = world.predators().iter().map(Animal::from).collect();
         let foods = world.foods().iter().map(Food::from).collect();
 
-        Self { animals, foods }
+        Self {
+            animals,
+            predators,
+            foods,
+        }
     }
 }
 
@@ -67,6 +163,8 @@ pub struct Animal {
     pub x: f32,
     pub y: f32,
     pub rotation: f32,
+    /// Animals caught by a predator or starved shouldn't be drawn
+    pub alive: bool,
 }
 
 impl From<&sim::Animal> for Animal {
@@ -75,6 +173,7 @@ impl From<&sim::Animal> for Animal {
             x: animal.position().x,
             y: animal.position().y,
             rotation: animal.rotation(),
+            alive: animal.is_alive(),
         }
     }
 }
@@ -94,3 +193,102 @@ impl From<&sim::Food> for Food {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    const SEED: u64 = 42;
+    const STEPS: usize = 300;
+
+    /// Short generations, so that `STEPS` runs through a few
+    /// rounds of selection, crossover and mutation too
+    fn simulation() -> Simulation {
+        let config = sim::Config {
+            sim_generation_length: 90,
+            world_predators: 3,
+            ..Default::default()
+        };
+
+        Simulation::from_config(SEED, config)
+    }
+
+    /// Everything in the world, birds and predators (whether
+    /// alive too) and foods, bit for bit
+    fn world_bits(world: &World) -> Vec<u32> {
+        let animals = world
+            .animals
+            .iter()
+            .chain(&world.predators)
+            .flat_map(|animal| {
+                [
+                    animal.x.to_bits(),
+                    animal.y.to_bits(),
+                    animal.rotation.to_bits(),
+                    animal.alive as u32,
+                ]
+            });
+
+        let foods = world
+            .foods
+            .iter()
+            .flat_map(|food| [food.x.to_bits(), food.y.to_bits()]);
+
+        animals.chain(foods).collect()
+    }
+
+    fn checksum(bits: &[u32]) -> u64 {
+        // FNV-1a
+        bits.iter().fold(0xcbf29ce484222325, |hash, &bits| {
+            (hash ^ bits as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    /// Both sides run on the same target, see
+    /// `world_is_stable_across_targets` for wasm against native
+    #[test]
+    fn bindings_do_not_change_the_run() {
+        let mut wasm = simulation();
+
+        let mut rng = ChaCha8Rng::seed_from_u64(SEED);
+        let mut native = sim::Simulation::random(wasm.sim.config().clone(), &mut rng);
+
+        for _ in 0..STEPS {
+            wasm.step();
+            native.step(&mut rng);
+
+            assert_eq!(
+                world_bits(&wasm.world()),
+                world_bits(&World::from(native.world()))
+            );
+        }
+    }
+
+    #[test]
+    fn with_config() {
+        let simulation = Simulation::with_config(SEED, r#"{ "world_predators": 3 }"#).unwrap();
+
+        assert_eq!(simulation.world().predators.len(), 3);
+        assert!(simulation.world().predators.iter().all(|p| p.alive));
+    }
+
+    /// The same checksum has to come out of every target, so running
+    /// `wasm-pack test --node libs/simulation-wasm` (as CI does) checks
+    /// the wasm build against what native produced. If this changes on
+    /// purpose (e.g. the simulation itself changed), update the expected value.
+    #[test]
+    fn world_is_stable_across_targets() {
+        let mut simulation = simulation();
+
+        for _ in 0..STEPS {
+            simulation.step();
+        }
+
+        assert_eq!(
+            checksum(&world_bits(&simulation.world())),
+            3409076807878201628
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! `cargo bench -p lib-simulation`, add `--features parallel`
+//! to see how stepping scales with cores
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use lib_simulation::{AnimalKind, Config, Eye, Food, Grid, World};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::hint::black_box;
+
+const SIZES: [usize; 3] = [500, 2000, 5000];
+
+fn config(size: usize) -> Config {
+    Config {
+        world_animals: size,
+        world_foods: size,
+        ..Default::default()
+    }
+}
+
+/// A whole step with as many birds as foods
+fn step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("world_step");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let config = config(size);
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut world = World::random(&config, &mut rng);
+
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| world.step(&config, &mut rng))
+        });
+    }
+
+    group.finish();
+}
+
+/// What every bird sees, by scanning all foods or only the nearby ones
+fn vision(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vision");
+
+    for size in SIZES {
+        let config = config(size);
+        let eye = Eye::from_config(&config, AnimalKind::Bird);
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let world = World::random(&config, &mut rng);
+
+        group.bench_function(BenchmarkId::new("scan", size), |b| {
+            b.iter(|| {
+                for animal in world.animals() {
+                    black_box(eye.process_channel(
+                        animal.position(),
+                        animal.rotation(),
+                        world.foods().iter().map(Food::position),
+                    ));
+                }
+            })
+        });
+
+        group.bench_function(BenchmarkId::new("grid", size), |b| {
+            b.iter(|| {
+                let mut grid = Grid::new(config.eye_fov_range / 4.);
+                grid.rebuild(world.foods().iter().map(Food::position).enumerate());
+
+                for animal in world.animals() {
+                    black_box(eye.process_channel(
+                        animal.position(),
+                        animal.rotation(),
+                        grid.nearby(animal.position(), eye.fov_range()),
+                    ));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, step, vision);
+criterion_main!(benches);
@@ -1,7 +1,16 @@
 use crate::*;
 
+/// Birds eat foods, predators hunt birds; each kind sees,
+/// thinks, flies and evolves by its own part of `Config`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimalKind {
+    Bird,
+    Predator,
+}
+
 #[derive(Debug)]
 pub struct Animal {
+    kind: AnimalKind,
     /// Contains co-ordinate of the animal within bounds 0..=1
     position: Vec2,
     /// Contains direction in terms of f32::consts::PI (180deg)
@@ -12,39 +21,83 @@ pub struct Animal {
     eye: Eye,
     /// Contains the neural network
     brain: Brain,
-    /// Contains the # of foods eaten
+    /// Contains the # of foods eaten, or birds caught by a predator
     satiation: usize,
+    /// Spent on flying and turning, gained by eating
+    energy: f32,
+    /// Contains the # of steps lived through
+    age: usize,
+    /// Animals caught by a predator or starved to death
+    /// stay dead until the next generation
+    alive: bool,
+    /// Contains the mutation step sizes inherited from its parents,
+    /// empty unless mutation is self-adaptive
+    step_sizes: Vec<f32>,
 }
 
 impl Animal {
-    pub fn new(eye: Eye, brain: Brain, rng: &mut dyn RngCore) -> Self {
+    /// How far from its parent a child is born; any closer and
+    /// they couldn't see each other
+    const BIRTH_DISTANCE: f32 = 0.01;
+
+    pub fn new(
+        config: &Config,
+        kind: AnimalKind,
+        eye: Eye,
+        brain: Brain,
+        rng: &mut dyn RngCore,
+    ) -> Self {
         Self {
+            kind,
             position: gen_vec2(rng),
             rotation: rng.gen_range(-PI..=PI),
             speed: 0.00005,
             eye,
             brain,
             satiation: 0,
+            energy: config.energy_initial,
+            age: 0,
+            alive: true,
+            step_sizes: Vec::new(),
         }
     }
 
-    pub fn random(rng: &mut dyn RngCore) -> Self {
-        let eye = Eye::default();
+    pub fn random(config: &Config, kind: AnimalKind, rng: &mut dyn RngCore) -> Self {
+        let eye = Eye::from_config(config, kind);
 
-        let brain = Brain::random(rng, &eye);
+        let brain = Brain::random(config, kind, rng, &eye);
 
-        Self::new(eye, brain, rng)
+        Self::new(config, kind, eye, brain, rng)
     }
 
     pub fn as_chromosome(&self) -> ga::Chromosome {
-        self.brain.as_chromosome()
+        self.brain
+            .as_chromosome()
+            .with_step_sizes(self.step_sizes.clone())
     }
 
-    pub fn from_chromosome(chromosome: ga::Chromosome, rng: &mut dyn RngCore) -> Self {
-        let eye = Eye::default();
-        let brain = Brain::from_chromosome(chromosome, &eye);
+    pub fn from_chromosome(
+        config: &Config,
+        kind: AnimalKind,
+        chromosome: ga::Chromosome,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        let eye = Eye::from_config(config, kind);
+        let step_sizes = chromosome.step_sizes().to_vec();
+        let brain = Brain::from_chromosome(config, kind, chromosome, &eye);
 
-        Self::new(eye, brain, rng)
+        Self {
+            step_sizes,
+            ..Self::new(config, kind, eye, brain, rng)
+        }
+    }
+
+    pub fn kind(&self) -> AnimalKind {
+        self.kind
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive
     }
 
     pub fn position(&self) -> Vec2 {
@@ -63,6 +116,14 @@ impl Animal {
         self.satiation
     }
 
+    pub fn energy(&self) -> f32 {
+        self.energy
+    }
+
+    pub fn age(&self) -> usize {
+        self.age
+    }
+
     pub fn set_position(&mut self, pos: Vec2) {
         self.position = pos;
     }
@@ -71,7 +132,8 @@ impl Animal {
         let rotation = self.rotation();
         let pos = self.position();
 
-        let angle_vector = Vec2::new(rotation.cos(), rotation.sin());
+        // libm rather than std so wasm and native runs agree
+        let angle_vector = Vec2::new(libm::cosf(rotation), libm::sinf(rotation));
         let displacement = angle_vector * self.speed();
 
         let new_pos = (pos + displacement).clamp(Vec2::splat(0.05), Vec2::splat(0.95));
@@ -81,29 +143,113 @@ impl Animal {
         self.set_position(new_pos)
     }
 
-    pub fn process_collisions(&mut self, food: &mut Food, rng: &mut dyn RngCore) {
+    pub fn process_collisions(&mut self, config: &Config, food: &mut Food, rng: &mut dyn RngCore) {
         let dist = self.position().distance(food.position());
 
-        if dist <= 0.01 {
+        if dist <= config.food_size {
             self.satiation += 1;
+            self.energy += config.energy_per_food;
             food.set_position(gen_vec2(rng));
         }
     }
 
-    pub fn process_brains(&mut self, foods: &[Food]) {
-        let vision = self
-            .eye
-            .process_vision(self.position(), self.rotation(), foods);
+    /// Lets a predator catch `bird`, if it's close enough
+    pub fn process_catch(&mut self, config: &Config, bird: &mut Animal) {
+        let dist = self.position().distance(bird.position());
+
+        if bird.alive && dist <= config.predator_catch_size {
+            self.satiation += 1;
+            self.energy += config.energy_per_food;
+            bird.alive = false;
+        }
+    }
 
+    /// `grids` index everything the animal may see, so that
+    /// only what's in range of the eye is looked at; `index` is
+    /// the animal's own, in `World::animals` or `World::predators`
+    pub fn process_brains(&mut self, config: &Config, grids: &Grids, index: usize) {
+        let vision = self.vision(grids, index);
         let response = self.brain.propogate(vision);
 
-        let speed = response[0].clamp(-MAX_ACCEL, MAX_ACCEL);
-        let rotation = response[1].clamp(-MAX_ROTATION, MAX_ROTATION);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(speed = response[0], rotation = response[1], "brain");
+
+        let speed = response[0].clamp(-config.sim_speed_accel, config.sim_speed_accel);
+        let rotation = response[1].clamp(-config.sim_rotation_accel, config.sim_rotation_accel);
 
         // We don't take the values absolute but add them
         // to existing values since our neural network doesn't
         // know absolute values of itself or food
-        self.speed = (self.speed() + speed).clamp(MIN_SPEED, MAX_SPEED);
+        let (speed_min, speed_max) = match self.kind {
+            AnimalKind::Bird => (config.sim_speed_min, config.sim_speed_max),
+            AnimalKind::Predator => (config.predator_speed_min, config.predator_speed_max),
+        };
+
+        self.speed = (self.speed() + speed).clamp(speed_min, speed_max);
         self.rotation = self.rotation() + rotation;
+
+        self.process_metabolism(config, rotation.abs());
+    }
+
+    /// What the animal sees of everything but itself, see `process_brains`
+    pub fn vision(&self, grids: &Grids, index: usize) -> Vec<f32> {
+        let (position, fov_range) = (self.position(), self.eye.fov_range());
+
+        self.eye
+            .process_vision(position, self.rotation(), |channel| {
+                grids.nearby(channel, position, fov_range, (self.kind, index))
+            })
+    }
+
+    /// Pays for the step just lived, flying at the new speed
+    /// and turning by `turn` radians, starving at no energy left
+    fn process_metabolism(&mut self, config: &Config, turn: f32) {
+        self.age += 1;
+        self.energy -= config.energy_step_cost
+            + config.energy_speed_cost * self.speed
+            + config.energy_turn_cost * turn;
+
+        if self.energy <= 0. {
+            self.energy = 0.;
+            self.alive = false;
+        }
+
+        // Only animals that aren't replaced every generation grow old
+        if config.sim_evolution == Evolution::SteadyState && self.age >= config.sim_lifespan {
+            self.alive = false;
+        }
+    }
+
+    pub fn can_reproduce(&self, config: &Config) -> bool {
+        self.alive && self.energy >= config.energy_reproduction
+    }
+
+    /// A child of this animal and `mate`, born right behind it
+    /// (or ahead, at the edge) and paid for with `energy_initial`
+    /// of its energy
+    pub fn reproduce(
+        &mut self,
+        config: &Config,
+        breeder: &Breeder,
+        mate: &ga::Chromosome,
+        rng: &mut dyn RngCore,
+    ) -> Animal {
+        let chromosome = breeder.breed(rng, &self.as_chromosome(), mate);
+        let mut child = Animal::from_chromosome(config, self.kind, chromosome, rng);
+
+        let rotation = self.rotation();
+        let offset = Vec2::new(libm::cosf(rotation), libm::sinf(rotation)) * Self::BIRTH_DISTANCE;
+        let behind = self.position - offset;
+
+        // Clamped like in `process_motion`
+        let inside = behind == behind.clamp(Vec2::splat(0.05), Vec2::splat(0.95));
+
+        self.energy -= config.energy_initial;
+        child.position = if inside {
+            behind
+        } else {
+            self.position + offset
+        };
+        child
     }
 }
@@ -23,14 +23,52 @@ impl ga::Individual for AnimalAgent {
 }
 
 impl AnimalAgent {
-    pub fn from_animal(animal: &Animal) -> Self {
+    pub fn from_animal(animal: &Animal, fitness: Fitness) -> Self {
+        let fitness = match fitness {
+            Fitness::Food => animal.satiation() as f32,
+            Fitness::Energy => animal.energy(),
+            Fitness::Survival => animal.age() as f32,
+        };
+
         Self {
-            fitness: animal.satiation() as f32,
+            fitness,
             chromosome: animal.as_chromosome(),
         }
     }
 
-    pub fn into_animal(self, rng: &mut dyn RngCore) -> Animal {
-        Animal::from_chromosome(self.chromosome, rng)
+    pub fn into_animal(self, config: &Config, kind: AnimalKind, rng: &mut dyn RngCore) -> Animal {
+        Animal::from_chromosome(config, kind, self.chromosome, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ga::Individual;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn fitness() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let config = Config {
+            world_animals: 1,
+            energy_step_cost: 0.01,
+            ..Default::default()
+        };
+
+        let mut world = World::random(&config, &mut rng);
+
+        for _ in 0..20 {
+            world.step(&config, &mut rng);
+        }
+
+        let animal = &world.animals[0];
+        let fitness = |fitness| AnimalAgent::from_animal(animal, fitness).fitness();
+
+        assert_eq!(fitness(Fitness::Food), animal.satiation() as f32);
+        assert_eq!(fitness(Fitness::Energy), animal.energy());
+        assert_eq!(fitness(Fitness::Survival), 20.);
     }
 }
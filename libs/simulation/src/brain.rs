@@ -6,8 +6,8 @@ pub struct Brain {
 }
 
 impl Brain {
-    pub fn random(rng: &mut dyn RngCore, eye: &Eye) -> Self {
-        let nn = nn::Network::random(rng, &Self::topology(eye));
+    pub fn random(config: &Config, kind: AnimalKind, rng: &mut dyn RngCore, eye: &Eye) -> Self {
+        let nn = nn::Network::random(rng, &Self::topology(config, kind, eye));
 
         Self { nn }
     }
@@ -16,21 +16,33 @@ impl Brain {
         self.nn.weights().collect()
     }
 
-    pub fn from_chromosome(chromosome: ga::Chromosome, eye: &Eye) -> Self {
+    pub fn from_chromosome(
+        config: &Config,
+        kind: AnimalKind,
+        chromosome: ga::Chromosome,
+        eye: &Eye,
+    ) -> Self {
         Self {
-            nn: nn::Network::from_weights(&Self::topology(eye), chromosome),
+            nn: nn::Network::from_weights(&Self::topology(config, kind, eye), chromosome),
         }
     }
 
-    pub fn topology(eye: &Eye) -> [nn::LayerTopology; 3] {
+    pub fn topology(config: &Config, kind: AnimalKind, eye: &Eye) -> [nn::LayerTopology; 3] {
+        let hidden_neurons = match kind {
+            AnimalKind::Bird => config.brain_neurons,
+            AnimalKind::Predator => config.predator_brain_neurons,
+        };
+
         [
             // The Input Layer
             //
             // Because our eye returns Vec<f32>, and our neural
             // network works on Vec<f32>, we can pass-through
-            // numbers from eye into the neural network directly.
+            // numbers from eye into the neural network directly,
+            // every channel's cells one after another.
             nn::LayerTopology {
-                neurons: eye.cells(),
+                neurons: eye.cells() * eye.channels().len(),
+                activation: nn::Activation::Identity,
             },
             // The Hidden Layer
             //
@@ -38,13 +50,19 @@ impl Brain {
             // layer that has somewhat more neurons that the input
             // layer and see how well the network performs.
             nn::LayerTopology {
-                neurons: 2 * eye.cells(),
+                neurons: hidden_neurons,
+                activation: nn::Activation::ReLU,
             },
             // The Output Layer
             //
             // Since the brain will control our bird's speed and
             // rotation, this gives us two numbers = two neurons.
-            nn::LayerTopology { neurons: 2 },
+            // Tanh lets both of them go negative, so the bird
+            // can slow down and turn either way.
+            nn::LayerTopology {
+                neurons: 2,
+                activation: nn::Activation::Tanh,
+            },
         ]
     }
 
@@ -0,0 +1,45 @@
+use crate::*;
+
+/// Crossover and mutation of one kind of animal, for
+/// breeding children one at a time in steady-state evolution
+pub struct Breeder {
+    crossover_method: Box<dyn ga::CrossoverMethod>,
+    mutation_method: Box<dyn ga::MutationMethod>,
+}
+
+impl Breeder {
+    pub fn new(
+        crossover_method: impl ga::CrossoverMethod + 'static,
+        mutation_method: impl ga::MutationMethod + 'static,
+    ) -> Self {
+        Self {
+            crossover_method: Box::new(crossover_method),
+            mutation_method: Box::new(mutation_method),
+        }
+    }
+
+    /// Retunes the mutation, as a generational run would after
+    /// every generation, from the statistics of every report
+    pub fn adapt(&mut self, stats: &ga::Statistics) {
+        self.mutation_method.adapt(stats);
+    }
+
+    pub fn mutation(&self) -> Option<ga::MutationParameters> {
+        self.mutation_method.parameters()
+    }
+
+    pub fn breed(
+        &self,
+        rng: &mut dyn RngCore,
+        parent_a: &ga::Chromosome,
+        parent_b: &ga::Chromosome,
+    ) -> ga::Chromosome {
+        ga::breed(
+            &*self.crossover_method,
+            &*self.mutation_method,
+            rng,
+            parent_a,
+            parent_b,
+        )
+    }
+}
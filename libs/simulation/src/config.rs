@@ -0,0 +1,619 @@
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::{error, fmt};
+
+/// Every knob of the simulation in one place. Missing
+/// fields fall back to their defaults when loading, so
+/// a config file only needs to list what it changes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Number of neurons in the brain's hidden layer
+    pub brain_neurons: usize,
+
+    /// How far an eye can see
+    pub eye_fov_range: f32,
+    /// How wide an eye can see, in radians
+    pub eye_fov_angle: f32,
+    /// Number of photoreceptors splitting the field of view
+    pub eye_cells: usize,
+    /// What an eye sees, each with its own `eye_cells`
+    pub eye_channels: Vec<Channel>,
+
+    /// Distance at which an animal eats a food
+    pub food_size: f32,
+
+    /// Energy every animal starts with
+    pub energy_initial: f32,
+    /// Energy gained from a food, or a bird for predators
+    pub energy_per_food: f32,
+    /// Energy spent every step just staying alive
+    pub energy_step_cost: f32,
+    /// Energy spent per unit of distance flown
+    pub energy_speed_cost: f32,
+    /// Energy spent per radian turned
+    pub energy_turn_cost: f32,
+    /// Energy an animal needs to have a child in steady-state
+    /// evolution, giving `energy_initial` of it to the child
+    pub energy_reproduction: f32,
+
+    /// What makes an animal fit
+    pub ga_fitness: Fitness,
+    /// How brains are mutated
+    pub ga_mutation: MutationKind,
+    /// Probability of a gene being mutated
+    pub ga_mut_chance: f32,
+    /// Magnitude of a single gene mutation
+    pub ga_mut_coeff: f32,
+    /// How `ga_mut_chance` and `ga_mut_coeff` are retuned
+    /// every generation, for Gaussian and normal mutation only
+    pub ga_mut_adaptation: MutationAdaptation,
+    /// Number of the fittest animals passed on unchanged,
+    /// in generational evolution only
+    pub ga_elite_count: usize,
+    /// Max distance between brains of one species, unset
+    /// to evolve the population without speciation (as
+    /// steady-state evolution always does)
+    pub ga_species_threshold: Option<f32>,
+    /// Which islands send animals to which
+    pub ga_migration_topology: MigrationTopology,
+    /// Number of generations between migrations
+    pub ga_migration_interval: usize,
+    /// Number of the fittest animals an island sends to each neighbour
+    pub ga_migrants: usize,
+    /// Fitness percentiles (0..=100) reported every generation
+    pub ga_percentiles: Vec<f32>,
+
+    /// Number of neurons in a predator brain's hidden layer
+    pub predator_brain_neurons: usize,
+    /// How far a predator's eye can see
+    pub predator_eye_fov_range: f32,
+    /// How wide a predator's eye can see, in radians
+    pub predator_eye_fov_angle: f32,
+    /// Number of photoreceptors splitting a predator's field of view
+    pub predator_eye_cells: usize,
+    /// What a predator's eye sees, each with its own `predator_eye_cells`
+    pub predator_eye_channels: Vec<Channel>,
+    /// Slowest a predator can fly
+    pub predator_speed_min: f32,
+    /// Fastest a predator can fly
+    pub predator_speed_max: f32,
+    /// Distance at which a predator catches a bird
+    pub predator_catch_size: f32,
+
+    /// Slowest an animal can fly
+    pub sim_speed_min: f32,
+    /// Fastest an animal can fly
+    pub sim_speed_max: f32,
+    /// Most an animal's speed can change in a single step
+    pub sim_speed_accel: f32,
+    /// Most an animal's rotation can change in a single step
+    pub sim_rotation_accel: f32,
+    /// The max age of birds before transferring
+    /// it's data into the ga
+    pub sim_generation_length: usize,
+    /// Whether every animal is replaced at once after
+    /// `sim_generation_length` steps, or one at a time
+    pub sim_evolution: Evolution,
+    /// Most steps an animal lives in steady-state evolution
+    pub sim_lifespan: usize,
+    /// How close a mate has to be in steady-state evolution;
+    /// animals with none in range have children on their own
+    pub sim_mate_range: f32,
+    /// Number of worlds evolving side by side as islands,
+    /// exchanging animals every `ga_migration_interval`
+    /// generations, or reports in steady-state evolution
+    pub sim_islands: usize,
+
+    pub world_animals: usize,
+    pub world_foods: usize,
+    /// Number of predators hunting the animals, none by default
+    pub world_predators: usize,
+}
+
+impl Config {
+    pub fn from_toml(toml: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(toml).map_err(ConfigError::Toml)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        let config: Self = serde_json::from_str(json).map_err(ConfigError::Json)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Catches values that would otherwise panic (or silently
+    /// misbehave) somewhere deep inside the simulation
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let checks = [
+            (self.brain_neurons > 0, "brain_neurons must be positive"),
+            (self.eye_fov_range > 0., "eye_fov_range must be positive"),
+            (self.eye_fov_angle > 0., "eye_fov_angle must be positive"),
+            (self.eye_cells > 0, "eye_cells must be positive"),
+            (
+                !self.eye_channels.is_empty(),
+                "eye_channels must not be empty",
+            ),
+            (self.food_size >= 0., "food_size must not be negative"),
+            (self.energy_initial > 0., "energy_initial must be positive"),
+            (
+                self.energy_reproduction >= self.energy_initial,
+                "energy_reproduction can't be below energy_initial",
+            ),
+            (
+                [
+                    self.energy_per_food,
+                    self.energy_step_cost,
+                    self.energy_speed_cost,
+                    self.energy_turn_cost,
+                ]
+                .iter()
+                .all(|&energy| energy >= 0.),
+                "energy gains and costs must not be negative",
+            ),
+            (
+                self.predator_brain_neurons > 0,
+                "predator_brain_neurons must be positive",
+            ),
+            (
+                self.predator_eye_fov_range > 0.,
+                "predator_eye_fov_range must be positive",
+            ),
+            (
+                self.predator_eye_fov_angle > 0.,
+                "predator_eye_fov_angle must be positive",
+            ),
+            (
+                self.predator_eye_cells > 0,
+                "predator_eye_cells must be positive",
+            ),
+            (
+                !self.predator_eye_channels.is_empty(),
+                "predator_eye_channels must not be empty",
+            ),
+            (
+                self.predator_speed_min >= 0.,
+                "predator_speed_min must not be negative",
+            ),
+            (
+                self.predator_speed_max.is_finite(),
+                "predator_speed_max must be finite",
+            ),
+            (
+                self.predator_speed_min <= self.predator_speed_max,
+                "predator_speed_min can't exceed predator_speed_max",
+            ),
+            (
+                self.predator_catch_size >= 0.,
+                "predator_catch_size must not be negative",
+            ),
+            (
+                self.ga_mutation != MutationKind::Normal || self.ga_mut_coeff >= 0.,
+                "ga_mut_coeff must not be negative for normal mutation",
+            ),
+            (
+                self.ga_mutation != MutationKind::SelfAdaptive || self.ga_mut_coeff > 0.,
+                "ga_mut_coeff must be positive for self-adaptive mutation",
+            ),
+            (
+                (0. ..=1.).contains(&self.ga_mut_chance),
+                "ga_mut_chance is on b/w 0 & 1",
+            ),
+            (
+                self.ga_mut_adaptation == MutationAdaptation::Fixed || self.ga_mut_coeff >= 0.,
+                "ga_mut_coeff must not be negative for adaptive mutation",
+            ),
+            (
+                self.ga_mut_adaptation.is_valid(),
+                "ga_mut_adaptation needs a factor above 1 and at least one generation",
+            ),
+            (
+                self.ga_species_threshold
+                    .is_none_or(|threshold| threshold >= 0.),
+                "ga_species_threshold must not be negative",
+            ),
+            (
+                self.ga_percentiles.iter().all(|p| (0. ..=100.).contains(p)),
+                "ga_percentiles are on b/w 0 & 100",
+            ),
+            (
+                self.ga_elite_count <= self.world_animals,
+                "ga_elite_count can't exceed world_animals",
+            ),
+            (
+                self.sim_speed_min >= 0.,
+                "sim_speed_min must not be negative",
+            ),
+            (
+                self.sim_speed_max.is_finite(),
+                "sim_speed_max must be finite",
+            ),
+            (
+                self.sim_speed_min <= self.sim_speed_max,
+                "sim_speed_min can't exceed sim_speed_max",
+            ),
+            (
+                self.sim_speed_accel >= 0.,
+                "sim_speed_accel must not be negative",
+            ),
+            (
+                self.sim_rotation_accel >= 0.,
+                "sim_rotation_accel must not be negative",
+            ),
+            (
+                self.ga_migration_interval > 0,
+                "ga_migration_interval must be positive",
+            ),
+            (
+                self.ga_migrants <= self.world_animals,
+                "ga_migrants can't exceed world_animals",
+            ),
+            (
+                self.sim_evolution != Evolution::SteadyState || self.ga_elite_count == 0,
+                "ga_elite_count has no effect in steady-state evolution",
+            ),
+            (
+                self.sim_evolution != Evolution::SteadyState || self.ga_species_threshold.is_none(),
+                "ga_species_threshold has no effect in steady-state evolution",
+            ),
+            (self.sim_islands > 0, "sim_islands must be positive"),
+            (self.sim_lifespan > 0, "sim_lifespan must be positive"),
+            (
+                self.sim_mate_range >= 0.,
+                "sim_mate_range must not be negative",
+            ),
+            (self.world_animals > 0, "world_animals must be positive"),
+        ];
+
+        match checks.iter().find(|(ok, _)| !ok) {
+            Some((_, reason)) => Err(ConfigError::Invalid(reason)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            brain_neurons: 18,
+
+            eye_fov_range: 0.25,
+            eye_fov_angle: PI + FRAC_PI_4,
+            eye_cells: 9,
+            eye_channels: vec![Channel::Food],
+
+            food_size: 0.01,
+
+            // Flying is free unless costs are set
+            energy_initial: 1.,
+            energy_per_food: 0.5,
+            energy_step_cost: 0.,
+            energy_speed_cost: 0.,
+            energy_turn_cost: 0.,
+            energy_reproduction: 2.,
+
+            ga_fitness: Fitness::Food,
+            ga_mutation: MutationKind::Gaussian,
+            ga_mut_chance: 0.01,
+            ga_mut_coeff: 0.3,
+            ga_mut_adaptation: MutationAdaptation::Fixed,
+            ga_elite_count: 0,
+            ga_species_threshold: None,
+            ga_migration_topology: MigrationTopology::Ring,
+            ga_migration_interval: 10,
+            ga_migrants: 2,
+            ga_percentiles: vec![10., 25., 75., 90.],
+
+            predator_brain_neurons: 18,
+            predator_eye_fov_range: 0.4,
+            predator_eye_fov_angle: FRAC_PI_2,
+            predator_eye_cells: 9,
+            predator_eye_channels: vec![Channel::Birds],
+            predator_speed_min: 0.0001,
+            predator_speed_max: 0.006,
+            predator_catch_size: 0.015,
+
+            sim_speed_min: 0.0001,
+            sim_speed_max: 0.005,
+            sim_speed_accel: 0.0005,
+            sim_rotation_accel: FRAC_PI_4,
+            sim_generation_length: 2500,
+            sim_evolution: Evolution::Generational,
+            sim_lifespan: 5000,
+            sim_mate_range: 0.1,
+            sim_islands: 1,
+
+            world_animals: 40,
+            world_foods: 40,
+            world_predators: 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Evolution {
+    /// Every `sim_generation_length` steps, all animals
+    /// are replaced by children of the fittest
+    #[default]
+    Generational,
+    /// Animals die of age, starvation or predators and have
+    /// children once they've got `energy_reproduction`, so the
+    /// world never resets; statistics are still reported every
+    /// `sim_generation_length` steps, and islands exchange animals
+    /// every `ga_migration_interval` of these reports
+    SteadyState,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fitness {
+    /// Number of foods eaten, or birds caught by predators
+    #[default]
+    Food,
+    /// Energy left at the end of the generation
+    Energy,
+    /// Number of steps lived through
+    Survival,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MutationKind {
+    /// Fixed `ga_mut_chance` and `ga_mut_coeff`; despite the
+    /// name, genes change by up to `ga_mut_coeff`, uniformly
+    #[default]
+    Gaussian,
+    /// Like `Gaussian`, but genes change by a normally distributed
+    /// amount, with `ga_mut_coeff` as its standard deviation
+    Normal,
+    /// Every animal carries its own step size, starting at
+    /// `ga_mut_coeff` and evolving along with its brain;
+    /// `ga_mut_chance` is ignored
+    SelfAdaptive,
+}
+
+/// See `ga::AdaptationRule` for how each rule works, e.g.
+/// `ga_mut_adaptation = { rule = "stagnation", generations = 10, factor = 1.5 }`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum MutationAdaptation {
+    #[default]
+    Fixed,
+    OneFifthSuccess {
+        window: usize,
+        factor: f32,
+    },
+    Diversity {
+        threshold: f32,
+        factor: f32,
+    },
+    Stagnation {
+        generations: usize,
+        factor: f32,
+    },
+}
+
+impl MutationAdaptation {
+    pub fn rule(&self) -> Option<ga::AdaptationRule> {
+        match *self {
+            Self::Fixed => None,
+            Self::OneFifthSuccess { window, factor } => {
+                Some(ga::AdaptationRule::OneFifthSuccess { window, factor })
+            }
+            Self::Diversity { threshold, factor } => {
+                Some(ga::AdaptationRule::Diversity { threshold, factor })
+            }
+            Self::Stagnation {
+                generations,
+                factor,
+            } => Some(ga::AdaptationRule::Stagnation {
+                generations,
+                factor,
+            }),
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        match *self {
+            Self::Fixed => true,
+            Self::OneFifthSuccess { window, factor } => window > 0 && factor > 1.,
+            Self::Diversity { factor, .. } => factor > 1.,
+            Self::Stagnation {
+                generations,
+                factor,
+            } => generations > 0 && factor > 1.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationTopology {
+    #[default]
+    Ring,
+    FullyConnected,
+    Random,
+}
+
+impl MigrationTopology {
+    pub fn topology(&self) -> ga::Topology {
+        match self {
+            Self::Ring => ga::Topology::Ring,
+            Self::FullyConnected => ga::Topology::FullyConnected,
+            Self::Random => ga::Topology::Random,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    Invalid(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Toml(err) => write!(f, "invalid TOML config: {err}"),
+            Self::Json(err) => write!(f, "invalid JSON config: {err}"),
+            Self::Invalid(reason) => write!(f, "invalid config: {reason}"),
+        }
+    }
+}
+
+impl error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Toml(err) => Some(err),
+            Self::Json(err) => Some(err),
+            Self::Invalid(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_toml() {
+        let config = Config::from_toml(
+            r#"
+            eye_cells = 13
+            eye_channels = ["food", "predators", "walls"]
+            ga_mutation = "self_adaptive"
+            world_animals = 100
+            sim_speed_max = 0.01
+            sim_islands = 4
+            sim_evolution = "steady_state"
+            ga_migration_topology = "fully_connected"
+            world_predators = 5
+            predator_speed_max = 0.008
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                eye_cells: 13,
+                eye_channels: vec![Channel::Food, Channel::Predators, Channel::Walls],
+                ga_mutation: MutationKind::SelfAdaptive,
+                world_animals: 100,
+                sim_speed_max: 0.01,
+                sim_islands: 4,
+                sim_evolution: Evolution::SteadyState,
+                ga_migration_topology: MigrationTopology::FullyConnected,
+                world_predators: 5,
+                predator_speed_max: 0.008,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn from_json() {
+        let config = Config::from_json(
+            r#"{ "food_size": 0.02, "ga_fitness": "survival", "ga_elite_count": 2, "ga_species_threshold": 4.5 }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                food_size: 0.02,
+                ga_fitness: Fitness::Survival,
+                ga_elite_count: 2,
+                ga_species_threshold: Some(4.5),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn mutation_adaptation() {
+        let config = Config::from_toml(
+            r#"ga_mut_adaptation = { rule = "stagnation", generations = 10, factor = 1.5 }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.ga_mut_adaptation.rule(),
+            Some(ga::AdaptationRule::Stagnation {
+                generations: 10,
+                factor: 1.5
+            })
+        );
+
+        assert!(matches!(
+            Config::from_toml(
+                r#"ga_mut_adaptation = { rule = "diversity", threshold = 1, factor = 0.5 }"#
+            ),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for (toml, reason) in [
+            ("eye_cells = 0", "eye_cells must be positive"),
+            ("sim_speed_min = -0.1", "sim_speed_min must not be negative"),
+            ("sim_speed_max = inf", "sim_speed_max must be finite"),
+            (
+                "sim_speed_accel = -0.1",
+                "sim_speed_accel must not be negative",
+            ),
+            (
+                "sim_rotation_accel = -0.1",
+                "sim_rotation_accel must not be negative",
+            ),
+            (
+                "predator_speed_min = -0.1",
+                "predator_speed_min must not be negative",
+            ),
+            (
+                "predator_speed_max = inf",
+                "predator_speed_max must be finite",
+            ),
+            (
+                "ga_mut_coeff = -0.1\nga_mut_adaptation = { rule = \"diversity\", threshold = 0.1, factor = 2.0 }",
+                "ga_mut_coeff must not be negative for adaptive mutation",
+            ),
+            (
+                "sim_evolution = \"steady_state\"\nga_elite_count = 1",
+                "ga_elite_count has no effect in steady-state evolution",
+            ),
+            (
+                "sim_evolution = \"steady_state\"\nga_species_threshold = 1.0",
+                "ga_species_threshold has no effect in steady-state evolution",
+            ),
+            (
+                "ga_mutation = \"normal\"\nga_mut_coeff = -0.1",
+                "ga_mut_coeff must not be negative for normal mutation",
+            ),
+        ] {
+            assert!(
+                matches!(Config::from_toml(toml), Err(ConfigError::Invalid(r)) if r == reason),
+                "{toml}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unparseable_files() {
+        assert!(matches!(
+            Config::from_toml("eye_cells = \"many\""),
+            Err(ConfigError::Toml(_))
+        ));
+        assert!(matches!(
+            Config::from_json("{ eye_cells: 9 }"),
+            Err(ConfigError::Json(_))
+        ));
+    }
+}
@@ -1,19 +1,31 @@
 use crate::*;
+use serde::{Deserialize, Serialize};
 
-const FOV_RANGE: f32 = 0.25;
-const FOV_ANGLE: f32 = PI + FRAC_PI_4;
-const CELLS: usize = 9;
+/// Something an eye can see; each channel
+/// gets its own `cells` photoreceptors
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Food,
+    /// Other living birds
+    Birds,
+    /// Other predators, as seen by predators
+    Predators,
+    /// Edges of the world
+    Walls,
+}
 
 #[derive(Debug)]
 pub struct Eye {
     fov_range: f32,
     fov_angle: f32,
     cells: usize,
+    channels: Vec<Channel>,
 }
 
 impl Eye {
-    fn new(fov_range: f32, fov_angle: f32, cells: usize) -> Self {
-        if !(fov_range > 0. && fov_angle > 0. && cells > 0) {
+    fn new(fov_range: f32, fov_angle: f32, cells: usize, channels: Vec<Channel>) -> Self {
+        if !(fov_range > 0. && fov_angle > 0. && cells > 0 && !channels.is_empty()) {
             panic!("wrong input to Eye vars");
         }
 
@@ -21,22 +33,78 @@ impl Eye {
             fov_range,
             fov_angle,
             cells,
+            channels,
+        }
+    }
+
+    pub fn from_config(config: &Config, kind: AnimalKind) -> Self {
+        match kind {
+            AnimalKind::Bird => Self::new(
+                config.eye_fov_range,
+                config.eye_fov_angle,
+                config.eye_cells,
+                config.eye_channels.clone(),
+            ),
+            AnimalKind::Predator => Self::new(
+                config.predator_eye_fov_range,
+                config.predator_eye_fov_angle,
+                config.predator_eye_cells,
+                config.predator_eye_channels.clone(),
+            ),
         }
     }
 
+    pub fn fov_range(&self) -> f32 {
+        self.fov_range
+    }
+
+    /// Number of cells of each channel
     pub fn cells(&self) -> usize {
         self.cells
     }
 
-    pub fn process_vision(&self, position: Vec2, rotation: f32, foods: &[Food]) -> Vec<f32> {
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels
+    }
+
+    /// Cells of every channel, one channel after another; `targets`
+    /// gives the positions of what a channel sees, e.g. of foods
+    pub fn process_vision(
+        &self,
+        position: Vec2,
+        rotation: f32,
+        targets: impl Fn(Channel) -> Vec<Vec2>,
+    ) -> Vec<f32> {
+        let cells: Vec<f32> = self
+            .channels
+            .iter()
+            .flat_map(|&channel| match channel {
+                Channel::Walls => self.process_walls(position, rotation),
+                channel => self.process_channel(position, rotation, targets(channel)),
+            })
+            .collect();
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(?position, rotation, ?cells, "vision");
+
+        cells
+    }
+
+    /// Cells of a single channel seeing whatever is at `targets`,
+    /// which mustn't include the animal itself
+    pub fn process_channel(
+        &self,
+        position: Vec2,
+        rotation: f32,
+        targets: impl IntoIterator<Item = Vec2>,
+    ) -> Vec<f32> {
         let mut cells: Vec<f32> = vec![0.; self.cells];
 
-        for food in foods {
-            let food_position = food.position();
+        for target in targets {
             let fov_range = self.fov_range;
             let fov_angle = self.fov_angle + 0.001; // rounding error or smth Idk
 
-            let vec = food_position - position;
+            let vec = target - position;
             let distance = vec.length();
             let angle = wrap_to_pi(Vec2::Y.angle_to(vec) - rotation);
 
@@ -50,31 +118,68 @@ impl Eye {
                 continue;
             }
 
-            // How far along the food is in the fov in range [0, 1]
+            // How far along the target is in the fov in range [0, 1]
             let cell = angle / fov_angle;
-            println!("{}", cell);
 
             // Get the index of cell by multiplying it with total cells
             let cell = cell * (self.cells as f32);
             let cell = (cell as usize).min(cells.len() - 1);
 
-            // The distance between the eyes and food
+            // The distance between the eyes and target
             // as an analogy for intensity of stimulation
             let cell_activation = (fov_range - distance) / fov_range;
 
             cells[cell] += cell_activation;
         }
-        println!("{:?}", cells);
+
         cells
     }
+
+    /// Each cell looks straight through its middle, the
+    /// closer the wall it finds, the stronger it activates
+    fn process_walls(&self, position: Vec2, rotation: f32) -> Vec<f32> {
+        (0..self.cells)
+            .map(|cell| {
+                let angle = (cell as f32 + 0.5) / self.cells as f32 * self.fov_angle
+                    - self.fov_angle / 2.
+                    + rotation;
+
+                // The inverse of `Vec2::Y.angle_to` above
+                let direction = Vec2::new(-libm::sinf(angle), libm::cosf(angle));
+                let distance = distance_to_wall(position, direction);
+
+                if distance >= self.fov_range {
+                    0.
+                } else {
+                    (self.fov_range - distance) / self.fov_range
+                }
+            })
+            .collect()
+    }
 }
 
 impl Default for Eye {
     fn default() -> Self {
-        Self::new(FOV_RANGE, FOV_ANGLE, CELLS)
+        Self::from_config(&Config::default(), AnimalKind::Bird)
     }
 }
 
+/// How far one has to go from `position` along `direction`
+/// to leave the world, i.e. the unit square
+fn distance_to_wall(position: Vec2, direction: Vec2) -> f32 {
+    let along = |position: f32, direction: f32| {
+        if direction > 0. {
+            (1. - position) / direction
+        } else if direction < 0. {
+            -position / direction
+        } else {
+            f32::INFINITY
+        }
+    };
+
+    along(position.x, direction.x).min(along(position.y, direction.y))
+}
+
 pub fn wrap_to_pi(angle: f32) -> f32 {
     if angle > PI {
         angle - 2. * PI
@@ -92,8 +197,11 @@ mod tests {
 
     const TEST_EYE_CELLS: usize = 13;
 
+    /// Channels that see targets all see them the same way
+    const TARGET_CHANNELS: [Channel; 3] = [Channel::Food, Channel::Birds, Channel::Predators];
+
     struct TestCase {
-        foods: Vec<Food>,
+        targets: Vec<Vec2>,
         fov_range: f32,
         fov_angle: f32,
         x: f32,
@@ -104,32 +212,42 @@ mod tests {
 
     impl TestCase {
         fn run(self) {
-            let eye = Eye::new(self.fov_range, self.fov_angle, TEST_EYE_CELLS);
-
-            let actual_vision =
-                eye.process_vision(Vec2::new(self.x, self.y), self.rotation, &self.foods);
-
-            let actual_vision: Vec<&str> = actual_vision
-                .into_iter()
-                .map(|cell| {
-                    if cell >= 0.6 {
-                        "#"
-                    } else if cell >= 0.3 {
-                        "+"
-                    } else if cell > 0. {
-                        "."
-                    } else {
-                        " "
-                    }
-                })
-                .collect();
-
-            let vision = actual_vision.join("");
-
-            assert_eq!(vision, self.expected_vision)
+            for channel in TARGET_CHANNELS {
+                let eye = Eye::new(
+                    self.fov_range,
+                    self.fov_angle,
+                    TEST_EYE_CELLS,
+                    vec![channel],
+                );
+
+                let actual_vision =
+                    eye.process_vision(Vec2::new(self.x, self.y), self.rotation, |seen| {
+                        assert_eq!(seen, channel);
+                        self.targets.clone()
+                    });
+
+                assert_eq!(render(&actual_vision), self.expected_vision, "{channel:?}");
+            }
         }
     }
 
+    fn render(vision: &[f32]) -> String {
+        vision
+            .iter()
+            .map(|&cell| {
+                if cell >= 0.6 {
+                    "#"
+                } else if cell >= 0.3 {
+                    "+"
+                } else if cell > 0. {
+                    "."
+                } else {
+                    " "
+                }
+            })
+            .collect()
+    }
+
     #[test_case(1.0, "      +      ")]
     #[test_case(0.9, "      +      ")]
     #[test_case(0.8, "      +      ")]
@@ -142,7 +260,7 @@ mod tests {
     #[test_case(0.1, "             ")]
     fn fov_ranges(fov_range: f32, expected_vision: &'static str) {
         TestCase {
-            foods: vec![food(0.5, 1.)],
+            targets: vec![target(0.5, 1.)],
             fov_angle: FRAC_PI_2,
             x: 0.5,
             y: 0.5,
@@ -163,15 +281,15 @@ mod tests {
     #[test_case(2.00 * PI, "+.  .+ +.  .+")] // FOV is the widest = 8 foods
     fn fov_angles(fov_angle: f32, expected_vision: &'static str) {
         TestCase {
-            foods: vec![
-                food(0.0, 0.0),
-                food(0.0, 0.33),
-                food(0.0, 0.66),
-                food(0.0, 1.0),
-                food(1.0, 0.0),
-                food(1.0, 0.33),
-                food(1.0, 0.66),
-                food(1.0, 1.0),
+            targets: vec![
+                target(0.0, 0.0),
+                target(0.0, 0.33),
+                target(0.0, 0.66),
+                target(0.0, 1.0),
+                target(1.0, 0.0),
+                target(1.0, 0.33),
+                target(1.0, 0.66),
+                target(1.0, 1.0),
             ],
             fov_range: 1.0,
             x: 0.5,
@@ -199,7 +317,7 @@ mod tests {
     #[test_case(2.50 * PI, "      +      ")]
     fn rotations(rotation: f32, expected_vision: &'static str) {
         TestCase {
-            foods: vec![food(0.0, 0.5)],
+            targets: vec![target(0.0, 0.5)],
             fov_range: 1.0,
             fov_angle: 2.0 * PI,
             x: 0.5,
@@ -237,7 +355,7 @@ mod tests {
     #[test_case(0.5, 1.0, "+            ")]
     fn positions(x: f32, y: f32, expected_vision: &'static str) {
         TestCase {
-            foods: vec![food(1.0, 0.4), food(1.0, 0.6)],
+            targets: vec![target(1.0, 0.4), target(1.0, 0.6)],
             fov_range: 1.0,
             fov_angle: FRAC_PI_2,
             rotation: 3.0 * FRAC_PI_2,
@@ -248,7 +366,66 @@ mod tests {
         .run()
     }
 
-    fn food(x: f32, y: f32) -> Food {
-        Food::new(Vec2::new(x, y))
+    // Looking from near the top wall (y = 1) ...
+    #[test_case(0.5, 0.9, 0.00 * PI, "#############")] // ... at it,
+    #[test_case(0.5, 0.9, 0.50 * PI, "##++.        ")] // ... to the left
+    #[test_case(0.5, 0.9, 1.50 * PI, "        .++##")] // ... to the right
+    #[test_case(0.5, 0.9, 1.00 * PI, "             ")]
+    // ... and away from it
+    //
+    // Looking at walls further away, which the
+    // edges of the field of view see the furthest
+    #[test_case(0.5, 0.7, 0.00 * PI, "..+++++++++..")]
+    #[test_case(0.3, 0.5, 0.50 * PI, "..+++++++++..")] // (the left wall)
+    #[test_case(0.5, 0.5, 0.00 * PI, "             ")]
+    //
+    // Looking into the top right corner, the right
+    // wall being further away than the top one
+    #[test_case(0.9, 0.9, 1.75 * PI, "#############")]
+    #[test_case(0.7, 0.9, 1.75 * PI, "++++#########")]
+    fn walls(x: f32, y: f32, rotation: f32, expected_vision: &'static str) {
+        let eye = Eye::new(0.5, FRAC_PI_2, TEST_EYE_CELLS, vec![Channel::Walls]);
+
+        let actual_vision = eye.process_vision(Vec2::new(x, y), rotation, |_| unreachable!());
+
+        assert_eq!(render(&actual_vision), expected_vision);
+    }
+
+    #[test]
+    fn channels_follow_each_other() {
+        let eye = Eye::new(
+            0.5,
+            FRAC_PI_2,
+            TEST_EYE_CELLS,
+            vec![
+                Channel::Food,
+                Channel::Birds,
+                Channel::Predators,
+                Channel::Walls,
+            ],
+        );
+
+        let vision = eye.process_vision(Vec2::new(0.5, 0.7), 0., |channel| match channel {
+            Channel::Food => vec![target(0.5, 0.9)],
+            Channel::Birds => vec![target(0.4, 0.9)],
+            Channel::Predators => Vec::new(),
+            Channel::Walls => unreachable!(),
+        });
+
+        let channels: Vec<String> = vision.chunks(TEST_EYE_CELLS).map(render).collect();
+
+        assert_eq!(
+            channels,
+            vec![
+                "      #      ",
+                "          +  ",
+                "             ",
+                "..+++++++++..",
+            ]
+        );
+    }
+
+    fn target(x: f32, y: f32) -> Vec2 {
+        Vec2::new(x, y)
     }
 }
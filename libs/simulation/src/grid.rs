@@ -0,0 +1,214 @@
+use crate::*;
+
+/// Buckets points of the unit square into square cells, so that
+/// everything near a point can be found without scanning every
+/// point. Points are referred to by their index, e.g. in
+/// `World::foods`, and keep a copy of their position.
+#[derive(Clone, Debug)]
+pub struct Grid {
+    /// Number of cells along each side
+    side: usize,
+    cell_size: f32,
+    cells: Vec<Vec<(usize, Vec2)>>,
+}
+
+impl Grid {
+    /// Caps the number of cells, however small they're asked to be
+    const MAX_SIDE: usize = 128;
+
+    pub fn new(cell_size: f32) -> Self {
+        if cell_size <= 0. {
+            panic!("Cell size must be positive")
+        }
+
+        let side = ((1. / cell_size).ceil() as usize).clamp(1, Self::MAX_SIDE);
+
+        Self {
+            side,
+            cell_size: 1. / side as f32,
+            cells: vec![Vec::new(); side * side],
+        }
+    }
+
+    /// Forgets every point and indexes `points` instead,
+    /// given as (index, position)
+    pub fn rebuild(&mut self, points: impl IntoIterator<Item = (usize, Vec2)>) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+
+        for (index, position) in points {
+            self.insert(index, position);
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, position: Vec2) {
+        let cell = self.cell(position);
+        self.cells[cell].push((index, position));
+    }
+
+    /// Follows a point that moved from `from` to `to`
+    pub fn relocate(&mut self, index: usize, from: Vec2, to: Vec2) {
+        let cell = self.cell(from);
+
+        if let Some(slot) = self.cells[cell].iter().position(|&(i, _)| i == index) {
+            self.cells[cell].swap_remove(slot);
+            self.insert(index, to);
+        }
+    }
+
+    /// Indices of every point within `radius` of `center`, in
+    /// ascending order, i.e. in the order a scan would find them
+    pub fn query(&self, center: Vec2, radius: f32) -> Vec<usize> {
+        self.within(center, radius)
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Positions of every point within `radius` of `center`,
+    /// in the same order as `query`
+    pub fn nearby(&self, center: Vec2, radius: f32) -> Vec<Vec2> {
+        self.within(center, radius)
+            .into_iter()
+            .map(|(_, position)| position)
+            .collect()
+    }
+
+    fn within(&self, center: Vec2, radius: f32) -> Vec<(usize, Vec2)> {
+        let (min_x, min_y) = self.coords(center - radius);
+        let (max_x, max_y) = self.coords(center + radius);
+
+        let mut points: Vec<(usize, Vec2)> = (min_y..=max_y)
+            .flat_map(|y| (min_x..=max_x).map(move |x| y * self.side + x))
+            .flat_map(|cell| &self.cells[cell])
+            .filter(|(_, position)| position.distance(center) <= radius)
+            .copied()
+            .collect();
+
+        points.sort_unstable_by_key(|&(index, _)| index);
+        points
+    }
+
+    /// Points outside of the unit square go to the nearest edge cell
+    fn coords(&self, position: Vec2) -> (usize, usize) {
+        let coord = |value: f32| ((value / self.cell_size) as usize).min(self.side - 1);
+
+        (coord(position.x), coord(position.y))
+    }
+
+    fn cell(&self, position: Vec2) -> usize {
+        let (x, y) = self.coords(position);
+
+        y * self.side + x
+    }
+}
+
+/// One grid per kind of thing an eye can see, rebuilt every step
+#[derive(Clone, Debug)]
+pub struct Grids {
+    pub foods: Grid,
+    /// Living birds only
+    pub birds: Grid,
+    /// Living predators only
+    pub predators: Grid,
+}
+
+impl Grids {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            foods: Grid::new(cell_size),
+            birds: Grid::new(cell_size),
+            predators: Grid::new(cell_size),
+        }
+    }
+
+    /// Positions of whatever `channel` sees within `radius` of
+    /// `center`, except for the `viewer` itself, given by its kind
+    /// and index; walls aren't indexed, so there are none of them
+    pub fn nearby(
+        &self,
+        channel: Channel,
+        center: Vec2,
+        radius: f32,
+        viewer: (AnimalKind, usize),
+    ) -> Vec<Vec2> {
+        let (grid, kind) = match channel {
+            Channel::Food => (&self.foods, None),
+            Channel::Birds => (&self.birds, Some(AnimalKind::Bird)),
+            Channel::Predators => (&self.predators, Some(AnimalKind::Predator)),
+            Channel::Walls => return Vec::new(),
+        };
+
+        // The grids are built before anyone moves, so the viewer
+        // may show up a step's length away from where it looks
+        let except = (kind == Some(viewer.0)).then_some(viewer.1);
+
+        grid.within(center, radius)
+            .into_iter()
+            .filter(|&(index, _)| Some(index) != except)
+            .map(|(_, position)| position)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn query_finds_what_a_scan_finds() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let points: Vec<Vec2> = (0..500).map(|_| gen_vec2(&mut rng)).collect();
+
+        let mut grid = Grid::new(0.07);
+        grid.rebuild(points.iter().copied().enumerate());
+
+        for radius in [0.01, 0.1, 0.25, 2.] {
+            for _ in 0..50 {
+                let center = gen_vec2_range(-0.2, 1.2, &mut rng);
+
+                let scanned: Vec<usize> = (0..points.len())
+                    .filter(|&i| points[i].distance(center) <= radius)
+                    .collect();
+
+                assert_eq!(
+                    grid.nearby(center, radius),
+                    scanned.iter().map(|&i| points[i]).collect::<Vec<_>>()
+                );
+                assert_eq!(grid.query(center, radius), scanned);
+            }
+        }
+    }
+
+    #[test]
+    fn query() {
+        let mut grid = Grid::new(0.1);
+        grid.rebuild([(0, Vec2::new(0.05, 0.05)), (1, Vec2::new(0.95, 0.95))]);
+
+        assert_eq!(grid.query(Vec2::new(0.1, 0.1), 0.1), vec![0]);
+        assert_eq!(grid.query(Vec2::new(0.5, 0.5), 0.65), vec![0, 1]);
+        assert_eq!(grid.query(Vec2::new(0.5, 0.5), 0.6), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn relocate() {
+        let mut grid = Grid::new(0.1);
+        grid.rebuild([(0, Vec2::new(0.05, 0.05)), (1, Vec2::new(0.15, 0.05))]);
+
+        grid.relocate(0, Vec2::new(0.05, 0.05), Vec2::new(0.95, 0.95));
+
+        assert_eq!(grid.query(Vec2::new(0.05, 0.05), 0.01), Vec::<usize>::new());
+        assert_eq!(grid.query(Vec2::new(0.95, 0.95), 0.01), vec![0]);
+        assert_eq!(grid.query(Vec2::new(0.15, 0.05), 0.01), vec![1]);
+    }
+
+    #[test]
+    fn tiny_cells_are_capped() {
+        let grid = Grid::new(1e-6);
+
+        assert_eq!(grid.side, Grid::MAX_SIDE);
+    }
+}
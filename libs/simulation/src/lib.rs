@@ -2,63 +2,201 @@
 mod animal;
 mod animal_agent;
 mod brain;
+mod breeder;
+mod config;
 mod eye;
 mod food;
+mod grid;
 mod world;
 
 use self::animal_agent::*;
-pub use self::{animal::*, brain::*, eye::*, food::*, world::*};
+pub use self::{animal::*, brain::*, breeder::*, config::*, eye::*, food::*, grid::*, world::*};
+use ga::Individual;
 use glam::Vec2;
 use lib_genetic_algorithm as ga;
 use lib_neural_network as nn;
 use rand::{Rng, RngCore};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::f32::consts::*;
 
-const MIN_SPEED: f32 = 0.0001;
-const MAX_SPEED: f32 = 0.005;
-const MAX_ACCEL: f32 = 0.0005;
-const MAX_ROTATION: f32 = FRAC_PI_4;
-// The max age of birds before transferring
-// it's data into the ga
-const GENERATION_LENGTH: usize = 2500;
-
 pub struct Simulation {
-    world: World,
-    ga: ga::GeneticAlgorithm<ga::RouletteWheelSelection>,
+    config: Config,
+    /// One world per island, evolved side by side
+    worlds: Vec<World>,
+    ga: ga::Islands<ga::RouletteWheelSelection>,
+    predator_ga: ga::Islands<ga::RouletteWheelSelection>,
+    /// Used instead of `ga` and `predator_ga` in steady-state evolution
+    breeder: Breeder,
+    predator_breeder: Breeder,
     age: usize,
+    /// Statistics reported so far in steady-state evolution,
+    /// which migrates every `ga_migration_interval` of them
+    periods: usize,
+}
+
+/// How each kind of animal did in a generation
+#[derive(Clone, Debug)]
+pub struct Statistics {
+    pub birds: ga::Statistics,
+    /// Unless there are no predators
+    pub predators: Option<ga::Statistics>,
 }
 
 impl Simulation {
-    pub fn random(rng: &mut dyn RngCore) -> Self {
-        let world = World::random(rng);
+    pub fn random(config: Config, rng: &mut dyn RngCore) -> Self {
+        let worlds = (0..config.sim_islands)
+            .map(|_| World::random(&config, rng))
+            .collect();
+
+        Self {
+            ga: Self::islands(&config, AnimalKind::Bird, config.ga_migrants),
+            predator_ga: Self::islands(
+                &config,
+                AnimalKind::Predator,
+                config.ga_migrants.min(config.world_predators),
+            ),
+            breeder: Breeder::new(
+                Self::crossover_method(&config, AnimalKind::Bird),
+                Self::mutation_method(&config),
+            ),
+            predator_breeder: Breeder::new(
+                Self::crossover_method(&config, AnimalKind::Predator),
+                Self::mutation_method(&config),
+            ),
+            config,
+            worlds,
+            age: 0,
+            periods: 0,
+        }
+    }
+
+    /// One genetic algorithm per world, each kind evolving on its own
+    fn islands(
+        config: &Config,
+        kind: AnimalKind,
+        migrants: usize,
+    ) -> ga::Islands<ga::RouletteWheelSelection> {
+        let algorithms = (0..config.sim_islands)
+            .map(|_| Self::genetic_algorithm(config, kind))
+            .collect();
 
-        let ga = ga::GeneticAlgorithm::new(
-            ga::RouletteWheelSelection,
-            ga::UniformCrossover,
-            ga::GaussianMutation::new(0.01, 0.3),
+        let migration = ga::Migration::new(
+            config.ga_migration_topology.topology(),
+            config.ga_migration_interval,
+            migrants,
         );
 
-        Self { world, ga, age: 0 }
+        ga::Islands::new(algorithms, migration)
+    }
+
+    fn genetic_algorithm(
+        config: &Config,
+        kind: AnimalKind,
+    ) -> ga::GeneticAlgorithm<ga::RouletteWheelSelection> {
+        let selection = ga::RouletteWheelSelection;
+        let crossover = Self::crossover_method(config, kind);
+        let mutation = Self::mutation_method(config);
+
+        let ga = ga::GeneticAlgorithm::new(selection, crossover, mutation)
+            .with_elitism(ga::Elitism::Count(config.ga_elite_count))
+            .with_percentiles(&config.ga_percentiles);
+
+        match config.ga_species_threshold {
+            Some(threshold) => ga.with_speciation(ga::Speciation::new(threshold)),
+            None => ga,
+        }
+    }
+
+    fn crossover_method(config: &Config, kind: AnimalKind) -> ga::NeuronCrossover {
+        // Swapping whole neurons keeps each one's bias and
+        // weights together, instead of scrambling them
+        let topology = Brain::topology(config, kind, &Eye::from_config(config, kind));
+
+        ga::NeuronCrossover::new(topology.iter().map(|layer| layer.neurons))
     }
 
+    fn mutation_method(config: &Config) -> Box<dyn ga::MutationMethod> {
+        match config.ga_mutation {
+            MutationKind::Gaussian => {
+                let gaussian = ga::GaussianMutation::new(config.ga_mut_chance, config.ga_mut_coeff);
+
+                match config.ga_mut_adaptation.rule() {
+                    Some(rule) => Box::new(ga::AdaptiveMutation::new(gaussian, rule)),
+                    None => Box::new(gaussian),
+                }
+            }
+            MutationKind::Normal => {
+                let normal = ga::NormalMutation::new(config.ga_mut_chance, config.ga_mut_coeff);
+
+                match config.ga_mut_adaptation.rule() {
+                    Some(rule) => Box::new(ga::AdaptiveMutation::new(normal, rule)),
+                    None => Box::new(normal),
+                }
+            }
+            MutationKind::SelfAdaptive => {
+                Box::new(ga::SelfAdaptiveMutation::new(config.ga_mut_coeff))
+            }
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The first island's world
     pub fn world(&self) -> &World {
-        &self.world
+        &self.worlds[0]
+    }
+
+    pub fn worlds(&self) -> &[World] {
+        &self.worlds
     }
 
-    pub fn step(&mut self, rng: &mut dyn RngCore) -> Option<ga::Statistics> {
-        self.world.step(rng);
+    pub fn step(&mut self, rng: &mut dyn RngCore) -> Option<Statistics> {
+        let steady_state = self.config.sim_evolution == Evolution::SteadyState;
+
+        for world in &mut self.worlds {
+            world.step(&self.config, rng);
+
+            if steady_state {
+                world.process_life_cycle(&self.config, &self.breeder, &self.predator_breeder, rng);
+            }
+        }
 
         self.age += 1;
 
-        if self.age > GENERATION_LENGTH {
-            Some(self.evolve(rng))
+        if self.age <= self.config.sim_generation_length {
+            return None;
+        }
+
+        if steady_state {
+            self.age = 0;
+            self.periods += 1;
+
+            let stats = self.statistics();
+
+            self.breeder.adapt(&stats.birds);
+
+            if let Some(predators) = &stats.predators {
+                self.predator_breeder.adapt(predators);
+            }
+
+            if self
+                .periods
+                .is_multiple_of(self.config.ga_migration_interval)
+            {
+                self.migrate(rng);
+            }
+
+            Some(stats)
         } else {
-            None
+            Some(self.evolve(rng))
         }
     }
 
     /// Fast forward to current generation's end
-    pub fn train(&mut self, rng: &mut dyn RngCore) -> ga::Statistics {
+    pub fn train(&mut self, rng: &mut dyn RngCore) -> Statistics {
         loop {
             if let Some(summary) = self.step(rng) {
                 return summary;
@@ -66,29 +204,162 @@ impl Simulation {
         }
     }
 
-    pub fn evolve(&mut self, rng: &mut dyn RngCore) -> ga::Statistics {
+    /// Evolves every island, returning statistics of each
+    /// kind's animals from all of the islands taken together
+    pub fn evolve(&mut self, rng: &mut dyn RngCore) -> Statistics {
         self.age = 0;
 
-        let current_poplu: Vec<_> = self
-            .world
-            .animals
-            .iter()
-            .map(AnimalAgent::from_animal)
-            .collect();
+        let (evolved_birds, birds) = self.ga.evolve(rng, &self.agents(World::animals));
 
-        let (evolved_poplu, stats) = self.ga.evolve(rng, &current_poplu);
+        let (evolved_predators, predators) = if self.config.world_predators > 0 {
+            let (evolved, stats) = self.predator_ga.evolve(rng, &self.agents(World::predators));
 
-        self.world.animals = evolved_poplu
-            .into_iter()
-            .map(|agent| agent.into_animal(rng))
-            .collect();
+            (evolved, Some(stats.overall))
+        } else {
+            (self.worlds.iter().map(|_| Vec::new()).collect(), None)
+        };
+
+        let stats = Statistics {
+            birds: birds.overall,
+            predators,
+        };
+
+        #[cfg(feature = "tracing")]
+        stats.trace();
 
-        for food in &mut self.world.foods {
-            food.set_position(gen_vec2(rng));
+        let evolved = evolved_birds.into_iter().zip(evolved_predators);
+
+        for (world, (birds, predators)) in self.worlds.iter_mut().zip(evolved) {
+            world.animals = birds
+                .into_iter()
+                .map(|agent| agent.into_animal(&self.config, AnimalKind::Bird, rng))
+                .collect();
+
+            for food in &mut world.foods {
+                food.set_position(gen_vec2(rng));
+            }
+
+            world.predators = predators
+                .into_iter()
+                .map(|agent| agent.into_animal(&self.config, AnimalKind::Predator, rng))
+                .collect();
         }
 
         stats
     }
+
+    /// Statistics of each kind's animals from all of the islands
+    /// taken together, without evolving them, along with the
+    /// mutation their children are bred with in steady state
+    pub fn statistics(&self) -> Statistics {
+        let of = |animals: fn(&World) -> &[Animal], breeder: &Breeder| {
+            let agents: Vec<AnimalAgent> = self.agents(animals).into_iter().flatten().collect();
+
+            (!agents.is_empty()).then(|| ga::Statistics {
+                mutation: breeder.mutation(),
+                ..ga::Statistics::new(&agents, &self.config.ga_percentiles)
+            })
+        };
+
+        let stats = Statistics {
+            birds: of(World::animals, &self.breeder).expect("birds never die out"),
+            predators: of(World::predators, &self.predator_breeder),
+        };
+
+        #[cfg(feature = "tracing")]
+        stats.trace();
+
+        stats
+    }
+
+    /// Steady-state counterpart of the islands' migration: copies
+    /// of every world's fittest animals replace the least fit ones
+    /// of the worlds they go to
+    fn migrate(&mut self, rng: &mut dyn RngCore) {
+        if self.worlds.len() < 2 {
+            return;
+        }
+
+        for kind in [AnimalKind::Bird, AnimalKind::Predator] {
+            let (animals, migrants): (fn(&World) -> &[Animal], _) = match kind {
+                AnimalKind::Bird => (World::animals, self.config.ga_migrants),
+                AnimalKind::Predator => (
+                    World::predators,
+                    self.config.ga_migrants.min(self.config.world_predators),
+                ),
+            };
+
+            let agents = self.agents(animals);
+
+            let migration = ga::Migration::new(
+                self.config.ga_migration_topology.topology(),
+                self.config.ga_migration_interval,
+                migrants,
+            );
+
+            let arrivals = migration.arrivals(rng, &agents);
+
+            for ((world, agents), arrivals) in self.worlds.iter_mut().zip(&agents).zip(arrivals) {
+                // Among the equally unfit, the newest born go first, since
+                // the fittest emigrate from the front of the same ties
+                let mut ranked: Vec<usize> = (0..agents.len()).collect();
+                ranked.sort_by(|&a, &b| {
+                    agents[a]
+                        .fitness()
+                        .total_cmp(&agents[b].fitness())
+                        .then(b.cmp(&a))
+                });
+
+                let population = match kind {
+                    AnimalKind::Bird => &mut world.animals,
+                    AnimalKind::Predator => &mut world.predators,
+                };
+
+                for (index, chromosome) in ranked.into_iter().zip(arrivals) {
+                    population[index] =
+                        Animal::from_chromosome(&self.config, kind, chromosome, rng);
+                }
+            }
+        }
+    }
+
+    /// Every island's animals, as picked by `animals`
+    fn agents(&self, animals: fn(&World) -> &[Animal]) -> Vec<Vec<AnimalAgent>> {
+        self.worlds
+            .iter()
+            .map(|world| {
+                animals(world)
+                    .iter()
+                    .map(|animal| AnimalAgent::from_animal(animal, self.config.ga_fitness))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Statistics {
+    fn trace(&self) {
+        for (kind, stats) in [
+            ("birds", Some(&self.birds)),
+            ("predators", self.predators.as_ref()),
+        ] {
+            let Some(stats) = stats else { continue };
+
+            tracing::info!(
+                kind,
+                min_fitness = stats.min_fitness,
+                max_fitness = stats.max_fitness,
+                avg_fitness = stats.avg_fitness,
+                median_fitness = stats.median_fitness,
+                diversity = stats.diversity,
+                species = stats.species.len(),
+                mut_chance = stats.mutation.map(|m| m.probability),
+                mut_coeff = stats.mutation.map(|m| m.magnitude),
+                "evolved"
+            );
+        }
+    }
 }
 
 pub fn gen_vec2(rng: &mut dyn RngCore) -> Vec2 {
@@ -98,3 +369,207 @@ pub fn gen_vec2(rng: &mut dyn RngCore) -> Vec2 {
 pub fn gen_vec2_range(start: f32, end: f32, rng: &mut dyn RngCore) -> Vec2 {
     Vec2::new(rng.gen_range(start..=end), rng.gen_range(start..=end))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn config(world_predators: usize) -> Config {
+        Config {
+            sim_generation_length: 50,
+            world_animals: 10,
+            world_predators,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn predators_evolve_alongside_birds() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut simulation = Simulation::random(config(4), &mut rng);
+
+        let stats = simulation.train(&mut rng);
+        let predators = stats.predators.unwrap();
+
+        assert_eq!(stats.birds.percentiles.len(), 4);
+        assert_eq!(predators.percentiles.len(), 4);
+        assert_eq!(simulation.world().animals().len(), 10);
+        assert_eq!(simulation.world().predators().len(), 4);
+        assert!(
+            simulation
+                .world()
+                .animals()
+                .iter()
+                .all(|bird| bird.is_alive())
+        );
+    }
+
+    #[test]
+    fn animals_see_every_channel() {
+        let every_channel = vec![
+            Channel::Food,
+            Channel::Birds,
+            Channel::Predators,
+            Channel::Walls,
+        ];
+
+        let config = Config {
+            eye_channels: every_channel.clone(),
+            predator_eye_channels: every_channel,
+            ..config(4)
+        };
+
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut simulation = Simulation::random(config, &mut rng);
+
+        simulation.train(&mut rng);
+        simulation.train(&mut rng);
+
+        assert_eq!(simulation.world().predators().len(), 4);
+    }
+
+    #[test]
+    fn no_predator_statistics_without_predators() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut simulation = Simulation::random(config(0), &mut rng);
+
+        assert!(simulation.train(&mut rng).predators.is_none());
+        assert!(simulation.world().predators().is_empty());
+    }
+
+    #[test]
+    fn steady_state_mutation_adapts() {
+        let config = Config {
+            sim_evolution: Evolution::SteadyState,
+            ga_mut_adaptation: MutationAdaptation::Stagnation {
+                generations: 1,
+                factor: 2.,
+            },
+            ..config(4)
+        };
+
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut simulation = Simulation::random(config, &mut rng);
+
+        // The first report sets the best fitness to improve on
+        let reports: Vec<ga::MutationParameters> = (0..3)
+            .map(|_| simulation.train(&mut rng).birds.mutation.unwrap())
+            .collect();
+
+        assert_eq!(reports[0].magnitude, 0.3);
+        assert_ne!(reports[2], reports[0]);
+    }
+
+    #[test]
+    fn steady_state_islands_exchange_animals() {
+        let config = Config {
+            sim_evolution: Evolution::SteadyState,
+            sim_islands: 2,
+            ga_migration_interval: 2,
+            ga_migrants: 1,
+            ..config(0)
+        };
+
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut simulation = Simulation::random(config, &mut rng);
+
+        let shared = |simulation: &Simulation| {
+            let chromosomes = |world: &World| -> Vec<ga::Chromosome> {
+                world.animals().iter().map(Animal::as_chromosome).collect()
+            };
+
+            let (a, b) = (
+                chromosomes(&simulation.worlds()[0]),
+                chromosomes(&simulation.worlds()[1]),
+            );
+
+            a.iter().filter(|chromosome| b.contains(chromosome)).count()
+        };
+
+        simulation.train(&mut rng);
+        assert_eq!(shared(&simulation), 0);
+
+        simulation.train(&mut rng);
+        assert_eq!(shared(&simulation), 2);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn thread_count_does_not_change_the_run() {
+        fn run(threads: usize) -> Vec<u32> {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+
+            pool.install(|| {
+                let mut rng = ChaCha8Rng::from_seed(Default::default());
+                let mut simulation = Simulation::random(config(4), &mut rng);
+
+                for _ in 0..3 {
+                    simulation.train(&mut rng);
+                }
+
+                for _ in 0..10 {
+                    simulation.step(&mut rng);
+                }
+
+                let world = simulation.world();
+                let animals = world.animals().iter().chain(world.predators());
+
+                animals
+                    .flat_map(|animal| {
+                        let position = animal.position();
+                        [position.x, position.y, animal.rotation(), animal.speed()]
+                    })
+                    .chain(
+                        world
+                            .foods()
+                            .iter()
+                            .flat_map(|food| food.position().to_array()),
+                    )
+                    .map(f32::to_bits)
+                    .collect()
+            })
+        }
+
+        assert_eq!(run(1), run(4));
+    }
+
+    #[test]
+    fn steady_state_evolution_keeps_the_world_going() {
+        let config = Config {
+            sim_evolution: Evolution::SteadyState,
+            energy_step_cost: 0.01,
+            ..config(4)
+        };
+
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut simulation = Simulation::random(config, &mut rng);
+
+        let stats = simulation.train(&mut rng);
+
+        assert_eq!(stats.birds.percentiles.len(), 4);
+        assert!(stats.predators.is_some());
+        assert!(simulation.world().animals().len() <= 10);
+        assert!(simulation.world().predators().len() <= 4);
+
+        // Nobody is reset when statistics are reported
+        assert!(
+            simulation
+                .world()
+                .predators()
+                .iter()
+                .any(|predator| predator.age() > 50)
+        );
+        assert!(
+            simulation
+                .worlds()
+                .iter()
+                .flat_map(World::animals)
+                .all(Animal::is_alive)
+        );
+    }
+}
@@ -2,34 +2,421 @@ use crate::*;
 
 #[derive(Debug)]
 pub struct World {
+    /// The birds
     pub animals: Vec<Animal>,
+    pub predators: Vec<Animal>,
     pub foods: Vec<Food>,
+    /// Where everything is, for eyes to look up
+    grids: Grids,
 }
 
 impl World {
-    pub fn random(rng: &mut dyn RngCore) -> Self {
-        let animals = (0..40).map(|_| Animal::random(rng)).collect();
+    pub fn random(config: &Config, rng: &mut dyn RngCore) -> Self {
+        let animals = (0..config.world_animals)
+            .map(|_| Animal::random(config, AnimalKind::Bird, rng))
+            .collect();
 
-        let foods = (0..40).map(|_| Food::random(rng)).collect();
+        let foods = (0..config.world_foods).map(|_| Food::random(rng)).collect();
 
-        Self { animals, foods }
+        let predators = (0..config.world_predators)
+            .map(|_| Animal::random(config, AnimalKind::Predator, rng))
+            .collect();
+
+        // A quarter of the shorter eye's range keeps both vision
+        // (a few cells across) and collisions (a cell or two) cheap
+        let grids = Grids::new(config.eye_fov_range.min(config.predator_eye_fov_range) / 4.);
+
+        Self {
+            animals,
+            predators,
+            foods,
+            grids,
+        }
     }
 
     pub fn animals(&self) -> &[Animal] {
         &self.animals
     }
 
+    pub fn predators(&self) -> &[Animal] {
+        &self.predators
+    }
+
     pub fn foods(&self) -> &[Food] {
         &self.foods
     }
 
-    pub fn step(&mut self, rng: &mut dyn RngCore) {
-        for animal in &mut self.animals {
-            animal.process_motion();
-            animal.process_brains(&self.foods);
-            for food in &mut self.foods {
-                animal.process_collisions(food, rng);
+    pub fn step(&mut self, config: &Config, rng: &mut dyn RngCore) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("world_step").entered();
+
+        // Foods may have been moved from outside, e.g. by evolution
+        self.grids
+            .foods
+            .rebuild(self.foods.iter().map(Food::position).enumerate());
+
+        self.rebuild_bird_grid();
+
+        self.grids.predators.rebuild(
+            self.predators
+                .iter()
+                .enumerate()
+                .filter(|(_, predator)| predator.is_alive())
+                .map(|(index, predator)| (index, predator.position())),
+        );
+
+        // Brains only read the grids, so every animal can think
+        // at once; eating and catching then run one animal after
+        // another, so who gets there first doesn't depend on
+        // thread count
+        let grids = &self.grids;
+
+        let think = |(index, animal): (usize, &mut Animal)| {
+            if animal.is_alive() {
+                animal.process_motion();
+                animal.process_brains(config, grids, index);
             }
+        };
+
+        #[cfg(feature = "parallel")]
+        let (birds, predators) = (
+            self.animals.par_iter_mut().enumerate(),
+            self.predators.par_iter_mut().enumerate(),
+        );
+
+        #[cfg(not(feature = "parallel"))]
+        let (birds, predators) = (
+            self.animals.iter_mut().enumerate(),
+            self.predators.iter_mut().enumerate(),
+        );
+
+        birds.for_each(think);
+        predators.for_each(think);
+
+        for animal in self.animals.iter_mut().filter(|bird| bird.is_alive()) {
+            for index in self.grids.foods.query(animal.position(), config.food_size) {
+                let food = &mut self.foods[index];
+                let position = food.position();
+
+                animal.process_collisions(config, food, rng);
+                self.grids.foods.relocate(index, position, food.position());
+            }
+        }
+
+        if self.predators.is_empty() {
+            return;
+        }
+
+        // Birds have moved since
+        self.rebuild_bird_grid();
+
+        for predator in self.predators.iter_mut().filter(|p| p.is_alive()) {
+            for index in self
+                .grids
+                .birds
+                .query(predator.position(), config.predator_catch_size)
+            {
+                predator.process_catch(config, &mut self.animals[index]);
+            }
+        }
+    }
+
+    /// Steady-state evolution: the dead make room for children
+    /// of those with energy to spare, up to `world_animals` birds
+    /// and `world_predators` predators
+    pub fn process_life_cycle(
+        &mut self,
+        config: &Config,
+        birds: &Breeder,
+        predators: &Breeder,
+        rng: &mut dyn RngCore,
+    ) {
+        Self::process_life_cycle_of(
+            config,
+            AnimalKind::Bird,
+            &mut self.animals,
+            config.world_animals,
+            birds,
+            rng,
+        );
+
+        Self::process_life_cycle_of(
+            config,
+            AnimalKind::Predator,
+            &mut self.predators,
+            config.world_predators,
+            predators,
+            rng,
+        );
+    }
+
+    fn process_life_cycle_of(
+        config: &Config,
+        kind: AnimalKind,
+        animals: &mut Vec<Animal>,
+        capacity: usize,
+        breeder: &Breeder,
+        rng: &mut dyn RngCore,
+    ) {
+        animals.retain(Animal::is_alive);
+
+        // A kind that died out starts over
+        if animals.is_empty() {
+            animals.extend((0..capacity).map(|_| Animal::random(config, kind, rng)));
+            return;
+        }
+
+        // Children born this step wait for the next one
+        for index in 0..animals.len() {
+            if animals.len() >= capacity {
+                break;
+            }
+
+            if !animals[index].can_reproduce(config) {
+                continue;
+            }
+
+            let mate = Self::nearest_mate(animals, index, config.sim_mate_range).unwrap_or(index);
+            let mate = animals[mate].as_chromosome();
+            let child = animals[index].reproduce(config, breeder, &mate, rng);
+
+            animals.push(child);
+        }
+    }
+
+    fn nearest_mate(animals: &[Animal], index: usize, range: f32) -> Option<usize> {
+        let position = animals[index].position();
+
+        (0..animals.len())
+            .filter(|&other| other != index)
+            .map(|other| (other, animals[other].position().distance(position)))
+            .filter(|&(_, distance)| distance <= range)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(other, _)| other)
+    }
+
+    fn rebuild_bird_grid(&mut self) {
+        self.grids.birds.rebuild(
+            self.animals
+                .iter()
+                .enumerate()
+                .filter(|(_, bird)| bird.is_alive())
+                .map(|(index, bird)| (index, bird.position())),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn world(rng: &mut dyn RngCore) -> (Config, World) {
+        let config = Config {
+            world_animals: 2,
+            world_foods: 0,
+            world_predators: 1,
+            ..Default::default()
+        };
+
+        let mut world = World::random(&config, rng);
+
+        world.animals[0].set_position(Vec2::new(0.5, 0.5));
+        world.animals[1].set_position(Vec2::new(0.1, 0.1));
+        world.predators[0].set_position(Vec2::new(0.5, 0.5));
+
+        (config, world)
+    }
+
+    #[test]
+    fn predators_catch_birds() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (config, mut world) = world(&mut rng);
+
+        world.step(&config, &mut rng);
+
+        assert!(!world.animals[0].is_alive());
+        assert!(world.animals[1].is_alive());
+        assert_eq!(world.predators[0].satiation(), 1);
+    }
+
+    #[test]
+    fn caught_birds_stay_put_and_stop_eating() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (config, mut world) = world(&mut rng);
+
+        world.step(&config, &mut rng);
+
+        let position = world.animals[0].position();
+        world.foods.push(Food::new(position));
+        world.predators[0].set_position(Vec2::new(0.9, 0.9));
+
+        for _ in 0..10 {
+            world.step(&config, &mut rng);
+        }
+
+        assert_eq!(world.animals[0].position(), position);
+        assert_eq!(world.animals[0].satiation(), 0);
+        assert_eq!(world.foods[0].position(), position);
+    }
+
+    #[test]
+    fn animals_starve_without_energy() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let config = Config {
+            world_animals: 1,
+            world_foods: 0,
+            energy_step_cost: 0.1,
+            ..Default::default()
+        };
+
+        let mut world = World::random(&config, &mut rng);
+
+        for _ in 0..9 {
+            world.step(&config, &mut rng);
         }
+
+        assert!(world.animals[0].is_alive());
+
+        world.step(&config, &mut rng);
+        let position = world.animals[0].position();
+        world.step(&config, &mut rng);
+
+        assert!(!world.animals[0].is_alive());
+        assert_eq!(world.animals[0].energy(), 0.);
+        assert_eq!(world.animals[0].age(), 10);
+        assert_eq!(world.animals[0].position(), position);
+    }
+
+    #[test]
+    fn flying_and_turning_cost_energy_eating_restores_it() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let config = Config {
+            world_animals: 1,
+            world_foods: 0,
+            energy_speed_cost: 1.,
+            energy_turn_cost: 1.,
+            ..Default::default()
+        };
+
+        let mut world = World::random(&config, &mut rng);
+
+        world.step(&config, &mut rng);
+
+        let energy = world.animals[0].energy();
+        assert!(energy < config.energy_initial);
+
+        let position = world.animals[0].position();
+        world.foods.push(Food::new(position));
+        world.step(&config, &mut rng);
+
+        assert_eq!(world.animals[0].satiation(), 1);
+        assert!(world.animals[0].energy() > energy);
+    }
+
+    #[test]
+    fn animals_do_not_see_themselves() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let config = Config {
+            world_animals: 1,
+            world_foods: 0,
+            eye_channels: vec![Channel::Birds],
+            ..Default::default()
+        };
+
+        let mut world = World::random(&config, &mut rng);
+
+        // The bird grid still holds where the bird was before it flew
+        world.step(&config, &mut rng);
+
+        let vision = world.animals[0].vision(&world.grids, 0);
+
+        assert_eq!(vision, vec![0.; config.eye_cells]);
+    }
+
+    fn breeder(config: &Config) -> Breeder {
+        let topology = Brain::topology(
+            config,
+            AnimalKind::Bird,
+            &Eye::from_config(config, AnimalKind::Bird),
+        );
+
+        Breeder::new(
+            ga::NeuronCrossover::new(topology.iter().map(|layer| layer.neurons)),
+            ga::GaussianMutation::new(0.01, 0.3),
+        )
+    }
+
+    #[test]
+    fn animals_with_energy_to_spare_give_birth() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let config = Config {
+            world_animals: 3,
+            world_foods: 0,
+            energy_reproduction: 1.,
+            sim_evolution: Evolution::SteadyState,
+            ..Default::default()
+        };
+
+        let breeder = breeder(&config);
+        let mut world = World::random(&config, &mut rng);
+
+        world.animals.truncate(1);
+        world.process_life_cycle(&config, &breeder, &breeder, &mut rng);
+
+        // Children born this step wait for the next one
+        assert_eq!(world.animals.len(), 2);
+        assert_eq!(world.animals[0].energy(), 0.);
+        assert_eq!(world.animals[1].energy(), config.energy_initial);
+
+        // Close, but not on top of each other, so that they can be seen
+        let distance = world.animals[1]
+            .position()
+            .distance(world.animals[0].position());
+        assert!((distance - 0.01).abs() < 1e-6);
+
+        world.process_life_cycle(&config, &breeder, &breeder, &mut rng);
+
+        assert_eq!(world.animals.len(), 3);
+
+        world.process_life_cycle(&config, &breeder, &breeder, &mut rng);
+
+        assert_eq!(world.animals.len(), 3);
+    }
+
+    #[test]
+    fn dead_animals_make_room_and_extinct_kinds_start_over() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (config, mut world) = world(&mut rng);
+        let breeder = breeder(&config);
+
+        world.step(&config, &mut rng);
+        world.process_life_cycle(&config, &breeder, &breeder, &mut rng);
+
+        assert_eq!(world.animals.len(), 1);
+        assert_eq!(world.predators.len(), 1);
+
+        let config = Config {
+            sim_evolution: Evolution::SteadyState,
+            sim_lifespan: 2,
+            ..config
+        };
+
+        world.step(&config, &mut rng);
+
+        assert!(!world.animals[0].is_alive());
+        assert!(!world.predators[0].is_alive());
+
+        world.process_life_cycle(&config, &breeder, &breeder, &mut rng);
+
+        assert_eq!(world.animals.len(), 2);
+        assert_eq!(world.predators.len(), 1);
+        assert!(world.animals.iter().all(|bird| bird.age() == 0));
+        assert_eq!(world.predators[0].age(), 0);
     }
 }
@@ -16,7 +16,7 @@ export function high_res_ctx(viewport) {
     return ctx;
 }
 
-export function draw_triangle(x, y, size, rotation) {
+export function draw_triangle(x, y, size, rotation, color = 'rgb(255, 255, 255)') {
     this.beginPath();
     
     this.moveTo(
@@ -39,7 +39,7 @@ export function draw_triangle(x, y, size, rotation) {
         y + Math.cos(rotation) * size * 1.5
     );
 
-    this.fillStyle = 'rgb(255, 255, 255)';
+    this.fillStyle = color;
     this.fill();
     // this.stroke();
 }